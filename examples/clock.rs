@@ -12,11 +12,15 @@ fn main() {
     for hour in 0..12 {
         let angle = (hour as f64) * std::f64::consts::PI / 6.0;
         let rotation = Matrix::rotation_z(angle);
-        let hour_point = rotation * Tuple::point(0.0, radius, 0.0);
-        let x = hour_point.0 + center.0;
-        let y = hour_point.1 + center.1;
-        let hour_point = Tuple::point(x, y, 0.0);
-        canvas.write_pixel(hour_point.0 as usize, hour_point.1 as usize, white);
+        let hour_point = rotation.clone() * Tuple::point(0.0, radius, 0.0);
+        let tick_start = rotation.clone() * Tuple::point(0.0, radius * 0.9, 0.0);
+        canvas.draw_line(
+            (tick_start.0 + center.0) as i64,
+            (tick_start.1 + center.1) as i64,
+            (hour_point.0 + center.0) as i64,
+            (hour_point.1 + center.1) as i64,
+            white,
+        );
     }
 
     canvas.save("clock.ppm").expect("Failed to save the canvas");
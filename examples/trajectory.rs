@@ -32,10 +32,13 @@ fn main() {
     };
     let mut c = Canvas::new(900, 550);
     let color = Tuple::color(1., 0., 0.);
+    let height = c.height as i64;
+    let to_canvas = |p: &Point| (p.0.round() as i64, height - p.1.round() as i64);
+    let mut last = to_canvas(&p.position);
     while p.position.1 > 0. {
-        let x = p.position.0.round() as usize;
-        let y = c.height - p.position.1.round() as usize;
-        c.write_pixel(x, y, color);
+        let next = to_canvas(&p.position);
+        c.draw_line(last.0, last.1, next.0, next.1, color);
+        last = next;
         p = tick(&e, p);
     }
     c.save("trajectory.ppm").unwrap();
@@ -0,0 +1,36 @@
+use crate::tuple::Tuple;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    // Swaps this axis's component with y. Shapes built around the y axis can
+    // run their math on the swapped tuple; applying it twice is a no-op.
+    pub fn swap_with_y(&self, t: Tuple) -> Tuple {
+        match self {
+            Axis::X => Tuple::new(t.1, t.0, t.2, t.3),
+            Axis::Y => t,
+            Axis::Z => Tuple::new(t.0, t.2, t.1, t.3),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapping_with_y_twice_gives_back_the_tuple() {
+        let p = Tuple::point(1.0, 2.0, 3.0);
+        assert_eq!(Axis::X.swap_with_y(p), Tuple::point(2.0, 1.0, 3.0));
+        assert_eq!(Axis::Y.swap_with_y(p), p);
+        assert_eq!(Axis::Z.swap_with_y(p), Tuple::point(1.0, 3.0, 2.0));
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            assert_eq!(axis.swap_with_y(axis.swap_with_y(p)), p);
+        }
+    }
+}
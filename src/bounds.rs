@@ -0,0 +1,133 @@
+use crate::{matrix::Matrix, tuple::{Point, Tuple}};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Bounds {
+        Bounds { min, max }
+    }
+
+    pub fn empty() -> Bounds {
+        Bounds::new(
+            Tuple::point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Tuple::point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+
+    pub fn infinite() -> Bounds {
+        Bounds::new(
+            Tuple::point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            Tuple::point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0 || self.min.1 > self.max.1 || self.min.2 > self.max.2
+    }
+
+    pub fn is_finite(&self) -> bool {
+        !self.is_empty() && self.min.as_array().iter().chain(self.max.as_array().iter()).all(|v| v.is_finite())
+    }
+
+    pub fn add_point(&mut self, point: Point) {
+        self.min = Tuple::point(self.min.0.min(point.0), self.min.1.min(point.1), self.min.2.min(point.2));
+        self.max = Tuple::point(self.max.0.max(point.0), self.max.1.max(point.1), self.max.2.max(point.2));
+    }
+
+    pub fn merge(&mut self, other: &Bounds) {
+        if !other.is_empty() {
+            self.add_point(other.min);
+            self.add_point(other.max);
+        }
+    }
+
+    pub fn center(&self) -> Point {
+        Tuple::point(
+            (self.min.0 + self.max.0) / 2.0,
+            (self.min.1 + self.max.1) / 2.0,
+            (self.min.2 + self.max.2) / 2.0,
+        )
+    }
+
+    // Box around the eight transformed corners. Infinite extents can't be
+    // pushed through a matrix without producing NaN, so they stay infinite.
+    pub fn transform(&self, m: &Matrix) -> Bounds {
+        if self.is_empty() {
+            return *self;
+        }
+        if !self.is_finite() {
+            return Bounds::infinite();
+        }
+
+        let mut bounds = Bounds::empty();
+        for x in [self.min.0, self.max.0] {
+            for y in [self.min.1, self.max.1] {
+                for z in [self.min.2, self.max.2] {
+                    bounds.add_point(m * &Tuple::point(x, y, z));
+                }
+            }
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{object::Object, shape::Shape, shapes::plane::Plane, transformation::Transformation};
+
+    use super::*;
+
+    #[test]
+    fn adding_points_to_empty_bounds() {
+        let mut b = Bounds::empty();
+        assert!(b.is_empty());
+        b.add_point(Tuple::point(-5.0, 2.0, 0.0));
+        b.add_point(Tuple::point(7.0, 0.0, -3.0));
+        assert_eq!(b.min, Tuple::point(-5.0, 0.0, -3.0));
+        assert_eq!(b.max, Tuple::point(7.0, 2.0, 0.0));
+        assert_eq!(b.center(), Tuple::point(1.0, 1.0, -1.5));
+    }
+
+    #[test]
+    fn transforming_bounds_encloses_the_rotated_box() {
+        let b = Bounds::new(Tuple::point(-1.0, -1.0, -1.0), Tuple::point(1.0, 1.0, 1.0));
+        let m = Matrix::identity().rotate_y(std::f64::consts::PI / 4.0).rotate_x(std::f64::consts::PI / 4.0);
+        let t = b.transform(&m);
+        let epsilon = 1e-4;
+        assert!((t.min.0 + 1.4142).abs() < epsilon);
+        assert!((t.min.1 + 1.7071).abs() < epsilon);
+        assert!((t.min.2 + 1.7071).abs() < epsilon);
+        assert!((t.max.0 - 1.4142).abs() < epsilon);
+        assert!((t.max.1 - 1.7071).abs() < epsilon);
+        assert!((t.max.2 - 1.7071).abs() < epsilon);
+    }
+
+    #[test]
+    fn a_plane_has_infinite_bounds() {
+        let p = Object::plane().with_transform(Matrix::identity().translate(0.0, -1.0, 0.0));
+        assert!(!p.bounds().is_finite());
+
+        let disc = Object::new(Shape::Plane(Plane::new().with_radius(3.0)));
+        assert_eq!(disc.bounds(), Bounds::new(Tuple::point(-3.0, 0.0, -3.0), Tuple::point(3.0, 0.0, 3.0)));
+    }
+
+    #[test]
+    fn a_group_bounds_its_transformed_children() {
+        let mut g = Object::group();
+        g.add_child(Object::sphere().with_transform(Matrix::identity().translate(2.0, 0.0, 0.0)));
+        g.add_child(Object::cylinder());
+        let g = g.with_transform(Matrix::identity().scale(2.0, 2.0, 2.0));
+        assert!(!g.bounds().is_finite());
+
+        let mut g = Object::group();
+        g.add_child(Object::sphere().with_transform(Matrix::identity().translate(2.0, 0.0, 0.0)));
+        g.add_child(Object::cube().with_transform(Matrix::identity().translate(0.0, 0.0, -3.0)));
+        let b = g.with_transform(Matrix::identity().scale(2.0, 2.0, 2.0)).bounds();
+        assert_eq!(b.min, Tuple::point(-2.0, -2.0, -8.0));
+        assert_eq!(b.max, Tuple::point(6.0, 2.0, 2.0));
+    }
+}
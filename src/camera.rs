@@ -1,7 +1,66 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::{canvas::Canvas, matrix::Matrix, ray::Ray, tuple::Tuple, world::World};
+use crate::{canvas::Canvas, color::Colors, matrix::Matrix, ray::Ray, render_stats::{RenderCounters, RenderStats}, sequence::{pixel_seed, CenteredSequence, RandomSequence, Sequence}, transformation::Transformation, tuple::{Color, Point, Tuple, Vector}, world::World};
+
+pub const TILE_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PixelFilter {
+    Box,
+    Tent,
+    Gaussian(f64),
+}
+
+impl PixelFilter {
+    // dx and dy are the sample's offset from the pixel center, in pixels.
+    pub fn weight(&self, dx: f64, dy: f64) -> f64 {
+        match self {
+            PixelFilter::Box => 1.0,
+            PixelFilter::Tent => (1.0 - dx.abs()).max(0.0) * (1.0 - dy.abs()).max(0.0),
+            PixelFilter::Gaussian(sigma) => (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp(),
+        }
+    }
+
+    pub fn resolve(&self, samples: &[(f64, f64, Color)]) -> Color {
+        let mut sum = Color::black();
+        let mut total = 0.0;
+        for &(dx, dy, color) in samples {
+            let weight = self.weight(dx, dy);
+            sum = sum + color * weight;
+            total += weight;
+        }
+        if total > 0.0 { sum / total } else { sum }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMap {
+    None,
+    Reinhard,
+    Exposure(f64),
+}
+
+impl ToneMap {
+    pub fn apply(&self, color: Color) -> Color {
+        match self {
+            ToneMap::None => color,
+            ToneMap::Reinhard => Tuple::color(color.0 / (1.0 + color.0), color.1 / (1.0 + color.1), color.2 / (1.0 + color.2)),
+            ToneMap::Exposure(exposure) => Tuple::color(
+                1.0 - (-color.0 * exposure).exp(),
+                1.0 - (-color.1 * exposure).exp(),
+                1.0 - (-color.2 * exposure).exp(),
+            ),
+        }
+    }
+}
 
 pub struct Camera {
     hsize: usize,
@@ -10,6 +69,12 @@ pub struct Camera {
     half_height: f64,
     pixel_size: f64,
     transform: Matrix,
+    samples: usize,
+    filter: PixelFilter,
+    seed: Option<u64>,
+    fill_color: Color,
+    projection: Projection,
+    tone_map: ToneMap,
 }
 
 impl Camera {
@@ -35,9 +100,57 @@ impl Camera {
             half_height,
             pixel_size,
             transform,
+            samples: 1,
+            filter: PixelFilter::Box,
+            seed: None,
+            fill_color: Colors::black(),
+            projection: Projection::Perspective,
+            tone_map: ToneMap::None,
         }
     }
 
+    // Parallel rays over an image plane world_width units across, for
+    // architectural and flat-looking renders.
+    pub fn orthographic(hsize: usize, vsize: usize, world_width: f64) -> Camera {
+        let mut camera = Camera::new(hsize, vsize, std::f64::consts::PI / 2.0);
+        camera.half_width = world_width / 2.0;
+        camera.half_height = camera.half_width * vsize as f64 / hsize as f64;
+        camera.pixel_size = world_width / hsize as f64;
+        camera.projection = Projection::Orthographic;
+        camera
+    }
+
+    pub fn look_at(hsize: usize, vsize: usize, field_of_view: f64, from: Point, to: Point, up: Vector) -> Camera {
+        Camera::new(hsize, vsize, field_of_view).with_transform(Matrix::view_transform(from, to, up))
+    }
+
+    // Points the camera at center from the given direction, backed off just
+    // far enough that a sphere of this radius fills the narrower side of the view.
+    pub fn frame(&mut self, center: Point, radius: f64, direction: Vector) {
+        let direction = direction.normalize();
+        let half_view = self.half_width.min(self.half_height);
+        let distance = radius / half_view.atan().sin();
+        let up = if direction.cross(Tuple::vector(0.0, 1.0, 0.0)).magnitude() < 1e-6 {
+            Tuple::vector(0.0, 0.0, 1.0)
+        } else {
+            Tuple::vector(0.0, 1.0, 0.0)
+        };
+        self.transform = Matrix::view_transform(center - direction * distance, center, up);
+    }
+
+    // Tone-maps every sample before the pixel filter averages them, so one
+    // very bright sub-sample can't blow out the whole pixel.
+    pub fn with_tone_map(mut self, tone_map: ToneMap) -> Camera {
+        self.tone_map = tone_map;
+        self
+    }
+
+    // Colour for tiles that render_with_budget runs out of time for.
+    pub fn with_fill_color(mut self, fill_color: Color) -> Camera {
+        self.fill_color = fill_color;
+        self
+    }
+
     pub fn with_transform(mut self, transform: Matrix) -> Camera {
         self.transform = transform;
         self
@@ -47,34 +160,296 @@ impl Camera {
         self.transform = transform;
     }
 
+    // Each pixel is sampled on a samples x samples stratified grid.
+    pub fn with_samples(mut self, samples: usize) -> Camera {
+        self.samples = samples.max(1);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: PixelFilter) -> Camera {
+        self.filter = filter;
+        self
+    }
+
+    // Without a seed, soft shadows use a fixed pattern; with one, each pixel
+    // gets its own reproducible jitter.
+    pub fn with_seed(mut self, seed: u64) -> Camera {
+        self.seed = Some(seed);
+        self
+    }
+
+    fn sequence_for_pixel(&self, px: usize, py: usize) -> Box<dyn Sequence> {
+        match self.seed {
+            Some(seed) => Box::new(RandomSequence::new(pixel_seed(px, py, seed))),
+            None => Box::new(CenteredSequence),
+        }
+    }
+
     pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
-        let xoffset = (px as f64 + 0.5) * self.pixel_size;
-        let yoffset = (py as f64 + 0.5) * self.pixel_size;
+        self.ray_for_pixel_offset(px, py, 0.5, 0.5)
+    }
+
+    pub fn ray_for_pixel_offset(&self, px: usize, py: usize, ox: f64, oy: f64) -> Ray {
+        let xoffset = (px as f64 + ox) * self.pixel_size;
+        let yoffset = (py as f64 + oy) * self.pixel_size;
         let world_x = self.half_width - xoffset;
         let world_y = self.half_height - yoffset;
-        let pixel = self.transform.inverse() * Tuple::point(world_x, world_y, -1.0);
-        let origin = self.transform.inverse() * Tuple::point(0.0, 0.0, 0.0);
-        let direction = (pixel - origin).normalize();
-        Ray::new(origin, direction)
+        let inverse = self.transform.inverse();
+        match self.projection {
+            Projection::Perspective => {
+                let pixel = &inverse * &Tuple::point(world_x, world_y, -1.0);
+                let origin = &inverse * &Tuple::point(0.0, 0.0, 0.0);
+                let direction = (pixel - origin).normalize();
+                Ray::new(origin, direction)
+            }
+            Projection::Orthographic => {
+                let origin = &inverse * &Tuple::point(world_x, world_y, 0.0);
+                let direction = (&inverse * &Tuple::vector(0.0, 0.0, -1.0)).normalize();
+                Ray::new(origin, direction)
+            }
+        }
     }
 
-    pub fn render(&self, world: &World) -> crate::canvas::Canvas {
-        let start = Instant::now();
+    // Debug view of texture coordinates: u in red, v in green, misses black.
+    pub fn render_uv(&self, world: &World) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let ray = self.ray_for_pixel(x, y);
+                if let Some(hit) = world.hit(&ray) {
+                    let (u, v) = hit.object.uv_at(&ray.position(hit.t));
+                    image.write_pixel(x, y, Tuple::color(u, v, 0.0));
+                }
+            }
+        }
+        image
+    }
+
+    // The world point distance units along the pixel's ray, the inverse of
+    // projecting that point onto the screen.
+    pub fn unproject(&self, px: usize, py: usize, distance: f64) -> Point {
+        self.ray_for_pixel(px, py).position(distance)
+    }
+
+    // Id of the object nearest the camera under the pixel, for selection.
+    pub fn pick(&self, world: &World, px: usize, py: usize) -> Option<usize> {
+        world.hit(&self.ray_for_pixel(px, py)).map(|hit| hit.object.id)
+    }
+
+    fn color_for_pixel(&self, world: &World, px: usize, py: usize, stats: Option<&RenderCounters>) -> Color {
+        let mut seq = self.sequence_for_pixel(px, py);
+        if self.samples == 1 {
+            if let Some(stats) = stats {
+                stats.add_primary_ray();
+            }
+            return self.tone_map.apply(world.color_at_with(&self.ray_for_pixel(px, py), 4, seq.as_mut(), stats));
+        }
+
+        let n = self.samples;
+        let mut samples = Vec::with_capacity(n * n);
+        for j in 0..n {
+            for i in 0..n {
+                let ox = (i as f64 + 0.5) / n as f64;
+                let oy = (j as f64 + 0.5) / n as f64;
+                if let Some(stats) = stats {
+                    stats.add_primary_ray();
+                }
+                let color = world.color_at_with(&self.ray_for_pixel_offset(px, py, ox, oy), 4, seq.as_mut(), stats);
+                samples.push((ox - 0.5, oy - 0.5, self.tone_map.apply(color)));
+            }
+        }
+        self.filter.resolve(&samples)
+    }
+
+    // Alpha is the fraction of the pixel's samples that hit something; misses
+    // contribute black.
+    fn rgba_for_pixel(&self, world: &World, px: usize, py: usize) -> (Color, f64) {
+        let mut seq = self.sequence_for_pixel(px, py);
+        if self.samples == 1 {
+            return match world.hit_color_at_with(&self.ray_for_pixel(px, py), seq.as_mut(), None) {
+                Some(color) => (self.tone_map.apply(color), 1.0),
+                None => (Colors::black(), 0.0),
+            };
+        }
+
+        let n = self.samples;
+        let mut samples = Vec::with_capacity(n * n);
+        let mut hits = 0;
+        for j in 0..n {
+            for i in 0..n {
+                let ox = (i as f64 + 0.5) / n as f64;
+                let oy = (j as f64 + 0.5) / n as f64;
+                let color = match world.hit_color_at_with(&self.ray_for_pixel_offset(px, py, ox, oy), seq.as_mut(), None) {
+                    Some(color) => {
+                        hits += 1;
+                        color
+                    }
+                    None => Colors::black(),
+                };
+                samples.push((ox - 0.5, oy - 0.5, self.tone_map.apply(color)));
+            }
+        }
+        (self.filter.resolve(&samples), hits as f64 / (n * n) as f64)
+    }
+
+    pub fn render_rgba(&self, world: &World) -> Canvas {
+        #[cfg(feature = "parallel")]
+        let pixels = (0..self.hsize * self.vsize).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let pixels = 0..self.hsize * self.vsize;
+        let rgba: Vec<(Color, f64)> = pixels
+            .map(|i| self.rgba_for_pixel(world, i % self.hsize, i / self.hsize))
+            .collect();
 
         let mut image = Canvas::new(self.hsize, self.vsize);
-        image.pixels_mut().par_chunks_mut(self.hsize).enumerate().for_each(|(y, row)| {
+        for (i, (color, alpha)) in rgba.into_iter().enumerate() {
+            image.pixels[i] = color;
+            image.alpha[i] = alpha;
+        }
+        image
+    }
+
+    // Blends one more jittered sample per pixel into accum so repeated calls
+    // converge on the pixel average. Jitter follows the R2 low-discrepancy
+    // sequence, which covers the pixel evenly for any number of passes.
+    pub fn render_accumulate(&self, world: &World, samples_so_far: usize, accum: &mut Canvas) {
+        let g = 1.324_717_957_244_746;
+        let k = samples_so_far as f64;
+        let ox = (0.5 + k / g).fract();
+        let oy = (0.5 + k / (g * g)).fract();
+        let weight = 1.0 / (samples_so_far + 1) as f64;
+
+        #[cfg(feature = "parallel")]
+        let rows = accum.pixels_mut().par_chunks_mut(self.hsize);
+        #[cfg(not(feature = "parallel"))]
+        let rows = accum.pixels_mut().chunks_mut(self.hsize);
+        rows.enumerate().for_each(|(y, row)| {
             for (x, pixel) in row.iter_mut().enumerate() {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray, 4);
-                *pixel = color;
+                let color = world.color_at(&self.ray_for_pixel_offset(x, y, ox, oy), 4);
+                *pixel = *pixel + (color - *pixel) * weight;
             }
         });
+    }
+
+    // The counters belong to this render alone, so other renders of the same
+    // world, even running at the same time, don't touch them.
+    pub fn render_with_stats(&self, world: &World) -> (Canvas, RenderStats) {
+        let counters = RenderCounters::new();
+        let image = self.render_counted(world, Some(&counters));
+        (image, counters.snapshot())
+    }
+
+    fn tiles(&self) -> Vec<(usize, usize, usize, usize)> {
+        let mut tiles = vec![];
+        for y in (0..self.vsize).step_by(TILE_SIZE) {
+            for x in (0..self.hsize).step_by(TILE_SIZE) {
+                tiles.push((x, y, TILE_SIZE.min(self.hsize - x), TILE_SIZE.min(self.vsize - y)));
+            }
+        }
+        tiles
+    }
+
+    // Renders the width x height block of pixels whose top-left corner is (x, y).
+    pub fn render_region(&self, world: &World, x: usize, y: usize, width: usize, height: usize) -> Canvas {
+        self.region_counted(world, x, y, width, height, None)
+    }
+
+    fn region_counted(&self, world: &World, x: usize, y: usize, width: usize, height: usize, stats: Option<&RenderCounters>) -> Canvas {
+        let mut region = Canvas::new(width, height);
+        for dy in 0..height {
+            for dx in 0..width {
+                region.write_pixel(dx, dy, self.color_for_pixel(world, x + dx, y + dy, stats));
+            }
+        }
+        region
+    }
+
+    // Tiles that haven't started once the budget runs out are left as the
+    // fill colour, so a preview always comes back on time.
+    pub fn render_with_budget(&self, world: &World, budget: Duration) -> Canvas {
+        let start = Instant::now();
+        #[cfg(feature = "parallel")]
+        let tiles = self.tiles().into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let tiles = self.tiles().into_iter();
+        let tiles: Vec<(usize, usize, usize, usize, Option<Canvas>)> = tiles
+            .map(|(x, y, width, height)| {
+                let tile = (start.elapsed() < budget).then(|| self.render_region(world, x, y, width, height));
+                (x, y, width, height, tile)
+            })
+            .collect();
+
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for (x0, y0, width, height, tile) in tiles {
+            for y in 0..height {
+                for x in 0..width {
+                    let color = match &tile {
+                        Some(tile) => tile.pixel_at(x, y),
+                        None => self.fill_color,
+                    };
+                    image.write_pixel(x0 + x, y0 + y, color);
+                }
+            }
+        }
+        image
+    }
+
+    // Renders only the objects on the given layer, for compositing passes.
+    pub fn render_layer(&self, world: &World, layer: u32) -> Canvas {
+        self.render(&world.on_layer(layer))
+    }
+
+    // Plain row-by-row loop with no threads. Pixels match render exactly,
+    // which makes it the one to reach for when debugging or on wasm.
+    pub fn render_single_threaded(&self, world: &World) -> Canvas {
+        self.single_threaded_counted(world, None)
+    }
+
+    fn single_threaded_counted(&self, world: &World, stats: Option<&RenderCounters>) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                image.write_pixel(x, y, self.color_for_pixel(world, x, y, stats));
+            }
+        }
+        image
+    }
 
+    pub fn render(&self, world: &World) -> crate::canvas::Canvas {
+        let start = Instant::now();
+        let image = self.render_counted(world, None);
         let duration = start.elapsed();
         println!("Render time: {:.2?}", duration);
 
         image
     }
+
+    fn render_counted(&self, world: &World, stats: Option<&RenderCounters>) -> Canvas {
+        #[cfg(feature = "parallel")]
+        return self.render_parallel(world, stats);
+        #[cfg(not(feature = "parallel"))]
+        return self.single_threaded_counted(world, stats);
+    }
+
+    #[cfg(feature = "parallel")]
+    fn render_parallel(&self, world: &World, stats: Option<&RenderCounters>) -> Canvas {
+        // Small tiles rather than whole rows, so rayon can balance dense and
+        // empty parts of the image across threads.
+        let tiles: Vec<(usize, usize, Canvas)> = self.tiles()
+            .into_par_iter()
+            .map(|(x, y, width, height)| (x, y, self.region_counted(world, x, y, width, height, stats)))
+            .collect();
+
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for (x0, y0, tile) in tiles {
+            for y in 0..tile.height {
+                for x in 0..tile.width {
+                    image.write_pixel(x0 + x, y0 + y, tile.pixel_at(x, y));
+                }
+            }
+        }
+        image
+    }
 }
 
 #[cfg(test)]
@@ -82,6 +457,9 @@ mod tests {
     use crate::tuple::Tuple;
     use crate::transformation::Transformation;
     use crate::world::World;
+    use crate::light::Light;
+    use crate::object::Object;
+    use crate::material::Material;
 
     use super::*;
 
@@ -150,6 +528,57 @@ mod tests {
         assert!((r.direction.2 + 2f64.sqrt() / 2.0).abs() < delta);
     }
 
+    #[test]
+    fn the_tent_filter_weights_the_center_more_than_the_corners() {
+        let filter = PixelFilter::Tent;
+        assert!(filter.weight(0.0, 0.0) > filter.weight(0.25, 0.25));
+        assert!(filter.weight(0.25, 0.25) > filter.weight(0.5, 0.5));
+        assert_eq!(filter.weight(0.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn the_gaussian_filter_falls_off_with_distance() {
+        let filter = PixelFilter::Gaussian(0.5);
+        assert_eq!(filter.weight(0.0, 0.0), 1.0);
+        assert!(filter.weight(0.0, 0.0) > filter.weight(0.5, 0.5));
+    }
+
+    #[test]
+    fn the_box_filter_is_the_plain_mean_of_a_2x2_grid() {
+        let w = World::default_world();
+        let c = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Transformation::view_transform(
+                Tuple::point(0.0, 0.0, -5.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ))
+            .with_samples(2);
+        let (px, py) = (3, 4);
+        let mut mean = Tuple::color(0.0, 0.0, 0.0);
+        for (ox, oy) in [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)] {
+            mean = mean + w.color_at(&c.ray_for_pixel_offset(px, py, ox, oy), 4);
+        }
+        mean = mean / 4.0;
+        let color = c.color_for_pixel(&w, px, py, None);
+        let delta = 1e-10;
+        assert!((color.0 - mean.0).abs() < delta);
+        assert!((color.1 - mean.1).abs() < delta);
+        assert!((color.2 - mean.2).abs() < delta);
+    }
+
+    #[test]
+    fn a_single_sample_matches_the_center_ray() {
+        let w = World::default_world();
+        let c = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Transformation::view_transform(
+                Tuple::point(0.0, 0.0, -5.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ))
+            .with_filter(PixelFilter::Tent);
+        assert_eq!(c.color_for_pixel(&w, 5, 5, None), w.color_at(&c.ray_for_pixel(5, 5), 4));
+    }
+
     #[test]
     fn rendering_a_world_with_a_camera() {
         let w = World::default_world();
@@ -165,4 +594,493 @@ mod tests {
         assert!((pixel.1 - 0.47583).abs() < delta);
         assert!((pixel.2 - 0.2855).abs() < delta);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn a_uv_render_of_a_plane_is_a_smooth_ramp() {
+        let w = World::new().with_objects(vec![Object::plane()]);
+        let c = Camera::orthographic(10, 10, 0.9).with_transform(Matrix::view_transform(
+            Tuple::point(0.5, 1.0, 0.5),
+            Tuple::point(0.5, 0.0, 0.5),
+            Tuple::vector(0.0, 0.0, 1.0),
+        ));
+        let image = c.render_uv(&w);
+
+        for i in 0..9 {
+            let (a, b) = (image.pixel_at(i, 5), image.pixel_at(i + 1, 5));
+            assert!((b.0 - a.0).abs() > 0.05 && (b.0 - a.0).abs() < 0.15);
+            assert!((b.1 - a.1).abs() < 1e-9);
+            let (a, b) = (image.pixel_at(5, i), image.pixel_at(5, i + 1));
+            assert!((b.1 - a.1).abs() > 0.05 && (b.1 - a.1).abs() < 0.15);
+            assert!((b.0 - a.0).abs() < 1e-9);
+        }
+        assert_eq!(image.pixel_at(5, 5).2, 0.0);
+    }
+
+    #[test]
+    fn a_uv_render_of_a_sphere_shows_latitude_and_longitude() {
+        let w = World::new().with_objects(vec![Object::sphere()]);
+        let c = Camera::new(21, 21, std::f64::consts::PI / 3.0).with_transform(Matrix::view_transform(
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        ));
+        let image = c.render_uv(&w);
+
+        // Latitude runs from the south pole (v = 0) to the north (v = 1).
+        let (top, middle, bottom) = (image.pixel_at(10, 8), image.pixel_at(10, 10), image.pixel_at(10, 12));
+        assert!(top.1 > middle.1 && middle.1 > bottom.1);
+        assert!((middle.1 - 0.5).abs() < 1e-9);
+
+        // The u = 0 seam faces the camera, so u jumps across the middle column.
+        let (left, right) = (image.pixel_at(8, 10), image.pixel_at(12, 10));
+        assert!(left.0.min(right.0) < 0.1 && left.0.max(right.0) > 0.9);
+
+        assert_eq!(image.pixel_at(0, 0), Colors::black());
+    }
+
+    #[test]
+    fn unprojecting_the_center_pixel() {
+        let c = Camera::new(11, 11, std::f64::consts::PI / 2.0);
+        assert_eq!(c.unproject(5, 5, 1.0), Tuple::point(0.0, 0.0, -1.0));
+        assert_eq!(c.unproject(5, 5, 3.0), Tuple::point(0.0, 0.0, -3.0));
+
+        let p1 = c.unproject(0, 0, 1.0);
+        let p2 = c.unproject(0, 0, 2.0);
+        assert!((p2.0 - 2.0 * p1.0).abs() < 1e-9);
+        assert!((p2.1 - 2.0 * p1.1).abs() < 1e-9);
+        assert!((p2.2 - 2.0 * p1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unprojecting_through_a_transformed_camera() {
+        let c = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Matrix::view_transform(Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)));
+        let p = c.unproject(5, 5, 5.0);
+        assert!((p - Tuple::point(0.0, 0.0, 0.0)).magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn a_quarter_roll_turns_the_image_a_quarter() {
+        let from = Tuple::point(0.0, 0.0, -5.0);
+        let to = Tuple::point(0.0, 0.0, 0.0);
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+        let plain = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Matrix::view_transform(from, to, up));
+        let rolled = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Matrix::view_transform_with_roll(from, to, up, std::f64::consts::PI / 2.0));
+
+        for y in 0..11 {
+            for x in 0..11 {
+                let a = plain.ray_for_pixel(x, y).direction;
+                let b = rolled.ray_for_pixel(10 - y, x).direction;
+                assert!((a - b).magnitude() < 1e-9);
+            }
+        }
+        assert_ne!(plain.ray_for_pixel(0, 5).direction, rolled.ray_for_pixel(0, 5).direction);
+    }
+
+    #[test]
+    fn single_threaded_and_parallel_renders_match() {
+        let w = World::default_world();
+        let mut c = Camera::new(40, 30, std::f64::consts::PI / 2.0).with_samples(2);
+        c.transform = Transformation::view_transform(
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        );
+        let single = c.render_single_threaded(&w);
+        let parallel = c.render(&w);
+        assert_eq!(single.pixels, parallel.pixels);
+        assert_eq!(single.to_ppm(), parallel.to_ppm());
+        assert_eq!(single.checksum(), parallel.checksum());
+        assert_eq!(c.render(&w).checksum(), parallel.checksum());
+    }
+
+    #[test]
+    fn tiled_render_matches_stitched_regions() {
+        let w = World::default_world();
+        let mut c = Camera::new(37, 21, std::f64::consts::PI / 2.0);
+        c.transform = Transformation::view_transform(
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        );
+        let image = c.render(&w);
+        let top = c.render_region(&w, 0, 0, 37, 10);
+        let bottom_left = c.render_region(&w, 0, 10, 20, 11);
+        let bottom_right = c.render_region(&w, 20, 10, 17, 11);
+        for y in 0..21 {
+            for x in 0..37 {
+                let expected = if y < 10 {
+                    top.pixel_at(x, y)
+                } else if x < 20 {
+                    bottom_left.pixel_at(x, y - 10)
+                } else {
+                    bottom_right.pixel_at(x - 20, y - 10)
+                };
+                assert_eq!(image.pixel_at(x, y), expected);
+                assert_eq!(image.pixel_at(x, y), c.color_for_pixel(&w, x, y, None));
+            }
+        }
+    }
+
+    #[test]
+    fn framing_a_unit_sphere_fills_the_view() {
+        let w = World::new().with_objects(vec![Object::sphere()]);
+        let mut c = Camera::new(101, 101, std::f64::consts::PI / 2.0);
+        c.frame(Tuple::point(0.0, 0.0, 0.0), 1.0, Tuple::vector(0.0, 0.0, 1.0));
+        let from = Tuple::point(0.0, 0.0, -2.0_f64.sqrt());
+        let expected = Matrix::view_transform(from, Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        for (a, b) in c.transform.data.iter().zip(expected.data.iter()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+
+        // The edge pixels graze the sphere, the corners miss it.
+        assert!(!w.intersect(&c.ray_for_pixel(0, 50)).is_empty());
+        assert!(!w.intersect(&c.ray_for_pixel(100, 50)).is_empty());
+        assert!(!w.intersect(&c.ray_for_pixel(50, 0)).is_empty());
+        assert!(w.intersect(&c.ray_for_pixel(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn framing_from_straight_above_picks_another_up_vector() {
+        let mut c = Camera::new(11, 11, std::f64::consts::PI / 2.0);
+        c.frame(Tuple::point(0.0, 0.0, 0.0), 1.0, Tuple::vector(0.0, -1.0, 0.0));
+        let r = c.ray_for_pixel(5, 5);
+        assert!(!r.direction.0.is_nan());
+        assert!((r.direction.1 + 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn a_generous_render_budget_matches_a_full_render() {
+        let w = World::default_world();
+        let c = Camera::new(33, 17, std::f64::consts::PI / 2.0)
+            .with_transform(Transformation::view_transform(
+                Tuple::point(0.0, 0.0, -5.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ));
+        let full = c.render(&w);
+        let budgeted = c.render_with_budget(&w, Duration::from_secs(600));
+        assert_eq!(budgeted.pixels, full.pixels);
+    }
+
+    #[test]
+    fn a_near_zero_render_budget_returns_the_fill_color() {
+        let w = World::default_world();
+        let fill = Tuple::color(1.0, 0.0, 1.0);
+        let c = Camera::new(64, 64, std::f64::consts::PI / 2.0)
+            .with_fill_color(fill)
+            .with_transform(Transformation::view_transform(
+                Tuple::point(0.0, 0.0, -5.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ));
+        let image = c.render_with_budget(&w, Duration::from_nanos(1));
+        let filled = image.pixels.iter().filter(|&&p| p == fill).count();
+        assert!(filled > image.pixels.len() / 2);
+    }
+
+    #[test]
+    fn rendering_a_layer_hides_objects_on_other_layers() {
+        let light = Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let front = Object::sphere()
+            .with_transform(Matrix::translation(0.0, 0.0, -2.0))
+            .with_material(Material::new().with_color(Tuple::color(1.0, 0.0, 0.0)).with_specular(0.0));
+        let back = Object::sphere()
+            .with_transform(Matrix::translation(0.0, 0.0, 2.0))
+            .with_material(Material::new().with_color(Tuple::color(0.0, 0.0, 1.0)).with_specular(0.0))
+            .with_layer(1);
+        let w = World::new().with_objects(vec![front, back]).with_lights(vec![light]);
+        let c = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Transformation::view_transform(
+                Tuple::point(0.0, 0.0, -8.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ));
+
+        // On the full render the red sphere blocks the blue one.
+        let full = c.render(&w).pixel_at(5, 5);
+        assert!(full.0 > 0.0 && full.2 == 0.0);
+
+        let layer = c.render_layer(&w, 1).pixel_at(5, 5);
+        assert!(layer.0 == 0.0 && layer.2 > 0.0);
+        assert_eq!(c.render_layer(&w, 1).pixel_at(0, 0), Tuple::color(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let c = Camera::orthographic(201, 101, 4.0)
+            .with_transform(Transformation::view_transform(
+                Tuple::point(0.0, 0.0, -5.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ));
+        let a = c.ray_for_pixel(100, 50);
+        let b = c.ray_for_pixel(0, 0);
+        assert_eq!(a.direction, Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(b.direction, a.direction);
+        assert_ne!(b.origin, a.origin);
+        assert_eq!(a.origin, Tuple::point(0.0, 0.0, -5.0));
+        // The top-left pixel sits half a pixel in from the corner of the
+        // 4 x (4 * 101 / 201) image plane; looking down +z, camera left is -x.
+        let pixel_size = 4.0 / 201.0;
+        let epsilon = 1e-10;
+        assert!((b.origin.0 + (2.0 - pixel_size / 2.0)).abs() < epsilon);
+        assert!((b.origin.1 - (2.0 * 101.0 / 201.0 - pixel_size / 2.0)).abs() < epsilon);
+        assert_eq!(b.origin.2, -5.0);
+    }
+
+    #[test]
+    fn an_orthographic_sphere_keeps_its_size_at_any_depth() {
+        let c = Camera::orthographic(21, 21, 4.0)
+            .with_transform(Transformation::view_transform(
+                Tuple::point(0.0, 0.0, -20.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ));
+        let covered = |z: f64| {
+            let w = World::new().with_objects(vec![Object::sphere().with_transform(Matrix::translation(0.0, 0.0, z))]);
+            let mut count = 0;
+            for y in 0..21 {
+                for x in 0..21 {
+                    if w.hit(&c.ray_for_pixel(x, y)).is_some() {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+        let near = covered(-10.0);
+        assert!(near > 0);
+        assert_eq!(covered(0.0), near);
+        assert_eq!(covered(10.0), near);
+    }
+
+    #[test]
+    fn picking_the_object_under_a_pixel() {
+        let s = Object::sphere();
+        let id = s.id;
+        let w = World::new().with_objects(vec![s]);
+        let c = Camera::look_at(11, 11, std::f64::consts::PI / 2.0,
+            Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(c.pick(&w, 5, 5), Some(id));
+        assert_eq!(c.pick(&w, 0, 0), None);
+    }
+
+    #[test]
+    fn tone_maps_compress_bright_values() {
+        let c = Tuple::color(0.0, 1.0, 3.0);
+        assert_eq!(ToneMap::None.apply(c), c);
+        assert_eq!(ToneMap::Reinhard.apply(c), Tuple::color(0.0, 0.5, 0.75));
+        let e = ToneMap::Exposure(1.0).apply(c);
+        assert!((e.1 - (1.0 - (-1.0_f64).exp())).abs() < 1e-10);
+        assert!(e.2 < 1.0);
+    }
+
+    #[test]
+    fn tone_mapping_each_sample_tames_a_firefly() {
+        let samples = [
+            (-0.25, -0.25, Tuple::color(0.1, 0.1, 0.1)),
+            (0.25, -0.25, Tuple::color(0.1, 0.1, 0.1)),
+            (-0.25, 0.25, Tuple::color(0.1, 0.1, 0.1)),
+            (0.25, 0.25, Tuple::color(100.0, 100.0, 100.0)),
+        ];
+        let after = ToneMap::Reinhard.apply(PixelFilter::Box.resolve(&samples));
+        let mapped: Vec<_> = samples.iter().map(|&(dx, dy, c)| (dx, dy, ToneMap::Reinhard.apply(c))).collect();
+        let before = PixelFilter::Box.resolve(&mapped);
+        assert!(after.0 > 0.95);
+        assert!(before.0 < 0.35);
+    }
+
+    #[test]
+    fn a_camera_tone_maps_samples_before_averaging() {
+        let glow = Object::sphere().with_material(Material::unlit(Tuple::color(0.0, 0.0, 0.0)).with_emissive(Tuple::color(50.0, 50.0, 50.0)));
+        let w = World::new().with_objects(vec![glow]).with_lights(vec![Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0))]);
+        let c = Camera::look_at(9, 9, std::f64::consts::PI / 2.0,
+            Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0))
+            .with_samples(3);
+        let raw = c.render(&w);
+        let mapped = c.with_tone_map(ToneMap::Reinhard).render(&w);
+
+        let mut compressed = 0;
+        for (raw, mapped) in raw.pixels.iter().zip(mapped.pixels.iter()) {
+            let post = ToneMap::Reinhard.apply(*raw);
+            assert!(mapped.0 <= post.0 + 1e-10);
+            if mapped.0 < post.0 - 0.05 {
+                compressed += 1;
+            }
+        }
+        assert!(compressed > 0);
+    }
+
+    #[test]
+    fn rendering_rgba_leaves_misses_transparent() {
+        let w = World::default_world();
+        let c = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Matrix::view_transform(Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)));
+        let image = c.render_rgba(&w);
+        assert_eq!(image.alpha_at(5, 5), 1.0);
+        assert_eq!(image.pixel_at(5, 5), w.color_at(&c.ray_for_pixel(5, 5), 4));
+        for (x, y) in [(0, 0), (10, 0), (0, 10), (10, 10)] {
+            assert_eq!(image.alpha_at(x, y), 0.0);
+            assert_eq!(image.pixel_at(x, y), Color::black());
+        }
+    }
+
+    #[test]
+    fn accumulated_samples_approach_a_supersampled_render() {
+        let w = World::default_world();
+        let camera = || Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Matrix::view_transform(Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)));
+
+        let mut accum = Canvas::new(11, 11);
+        for k in 0..64 {
+            camera().render_accumulate(&w, k, &mut accum);
+        }
+        let reference = camera().with_samples(8).render(&w);
+
+        for (a, b) in accum.pixels.iter().zip(&reference.pixels) {
+            assert!((a.0 - b.0).abs() < 0.05);
+            assert!((a.1 - b.1).abs() < 0.05);
+            assert!((a.2 - b.2).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn the_first_accumulated_sample_replaces_the_buffer() {
+        let w = World::default_world();
+        let c = Camera::new(5, 5, std::f64::consts::PI / 2.0)
+            .with_transform(Matrix::view_transform(Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)));
+        let mut accum = Canvas::new(5, 5);
+        for p in accum.pixels_mut() {
+            *p = Tuple::color(9.0, 9.0, 9.0);
+        }
+        c.render_accumulate(&w, 0, &mut accum);
+        let expected = w.color_at(&c.ray_for_pixel_offset(2, 2, 0.5, 0.5), 4);
+        let p = accum.pixel_at(2, 2);
+        assert!((p.0 - expected.0).abs() < 1e-9);
+        assert!((p.1 - expected.1).abs() < 1e-9);
+        assert!((p.2 - expected.2).abs() < 1e-9);
+    }
+
+    fn soft_shadow_scene() -> (World, Camera) {
+        let light = Light::new(Tuple::point(-5.0, 10.0, -5.0), Tuple::color(1.0, 1.0, 1.0)).with_softness(2.0);
+        let world = World::new()
+            .with_lights(vec![light])
+            .with_objects(vec![
+                Object::plane(),
+                Object::sphere().with_transform(Matrix::translation(0.0, 1.0, 0.0)),
+            ]);
+        let camera = Camera::new(16, 12, std::f64::consts::PI / 3.0)
+            .with_transform(Matrix::view_transform(Tuple::point(0.0, 4.0, -8.0), Tuple::point(1.0, 0.0, 1.0), Tuple::vector(0.0, 1.0, 0.0)));
+        (world, camera)
+    }
+
+    #[test]
+    fn renders_with_the_same_seed_are_identical() {
+        let (world, camera) = soft_shadow_scene();
+        let a = camera.with_seed(7).render(&world);
+        let (_, camera) = soft_shadow_scene();
+        let b = camera.with_seed(7).render(&world);
+        assert_eq!(a.pixels, b.pixels);
+    }
+
+    #[test]
+    fn renders_with_different_seeds_differ_only_in_noise() {
+        let (world, camera) = soft_shadow_scene();
+        let a = camera.with_seed(7).render(&world);
+        let (_, camera) = soft_shadow_scene();
+        let b = camera.with_seed(8).render(&world);
+        assert_ne!(a.pixels, b.pixels);
+
+        let n = a.pixels.len() as f64;
+        let mean = |c: &Canvas| c.pixels.iter().fold(Tuple::color(0.0, 0.0, 0.0), |acc, p| acc + *p) / n;
+        let (ma, mb) = (mean(&a), mean(&b));
+        assert!((ma.0 - mb.0).abs() < 0.02);
+        assert!((ma.1 - mb.1).abs() < 0.02);
+        assert!((ma.2 - mb.2).abs() < 0.02);
+
+        // Only the penumbra is noisy; fully lit and fully shadowed pixels
+        // come out the same under any seed.
+        let changed = a.pixels.iter().zip(&b.pixels).filter(|(p, q)| p != q).count();
+        assert!(changed < a.pixels.len() / 2);
+    }
+
+    #[test]
+    fn a_seeded_rgba_render_matches_the_seeded_render() {
+        let (world, camera) = soft_shadow_scene();
+        let camera = camera.with_seed(7);
+        let rgb = camera.render(&world);
+        let rgba = camera.render_rgba(&world);
+        assert_eq!(rgba.pixels, rgb.pixels);
+
+        let camera = camera.with_samples(2);
+        assert_eq!(camera.render_rgba(&world).pixels, camera.render(&world).pixels);
+    }
+
+    #[test]
+    fn a_seeded_region_matches_the_full_render() {
+        let (world, camera) = soft_shadow_scene();
+        let full = camera.with_seed(7).render(&world);
+        let (_, camera) = soft_shadow_scene();
+        let region = camera.with_seed(7).render_region(&world, 4, 3, 6, 5);
+        for y in 0..5 {
+            for x in 0..6 {
+                assert_eq!(region.pixel_at(x, y), full.pixel_at(x + 4, y + 3));
+            }
+        }
+    }
+
+    #[test]
+    fn an_unseeded_render_matches_the_default_shading() {
+        let (world, camera) = soft_shadow_scene();
+        let image = camera.render(&world);
+        let (_, camera) = soft_shadow_scene();
+        assert_eq!(image.pixel_at(8, 8), world.color_at(&camera.ray_for_pixel(8, 8), 4));
+    }
+
+    #[test]
+    fn look_at_matches_a_manual_view_transform() {
+        let from = Tuple::point(0.0, 1.5, -5.0);
+        let to = Tuple::point(0.0, 1.0, 0.0);
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+        let c = Camera::look_at(200, 125, std::f64::consts::PI / 3.0, from, to, up);
+        let manual = Camera::new(200, 125, std::f64::consts::PI / 3.0)
+            .with_transform(Matrix::view_transform(from, to, up));
+        assert_eq!(c.transform, manual.transform);
+        assert_eq!(c.pixel_size, manual.pixel_size);
+    }
+
+    #[test]
+    fn concurrent_renders_of_one_world_keep_their_own_stats() {
+        let world = World::default_world();
+        let c = Camera::look_at(8, 6, std::f64::consts::PI / 3.0, Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        let (_, alone) = c.render_with_stats(&world);
+        let (a, b) = std::thread::scope(|scope| {
+            let a = scope.spawn(|| c.render_with_stats(&world).1);
+            let b = scope.spawn(|| c.render_with_stats(&world).1);
+            (a.join().unwrap(), b.join().unwrap())
+        });
+        assert_eq!(alone.primary_rays, 48);
+        assert_eq!(a, alone);
+        assert_eq!(b, alone);
+    }
+
+    #[test]
+    fn rendering_one_pixel_of_a_sphere_reports_its_rays() {
+        let world = World::new()
+            .with_objects(vec![Object::sphere()])
+            .with_lights(vec![Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0))]);
+        let c = Camera::look_at(1, 1, std::f64::consts::PI / 4.0, Tuple::point(0.0, 0.0, -5.0), Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        let (_, stats) = c.render_with_stats(&world);
+        assert_eq!(stats.primary_rays, 1);
+        assert_eq!(stats.shadow_rays, 1);
+        assert_eq!(stats.reflection_rays, 0);
+        assert_eq!(stats.intersection_tests, 2);
+
+        // Each render counts for itself.
+        let (_, again) = c.render_with_stats(&world);
+        assert_eq!(again, stats);
+    }
+}
@@ -4,12 +4,18 @@ pub struct Canvas {
     pub width: usize,
     pub height: usize,
     pub pixels: Vec<Tuple>,
+    pub alpha: Vec<f64>,
 }
 
 impl Canvas {
     pub fn new(width: usize, height: usize) -> Canvas {
-        let pixels = vec![Tuple::color(0.0, 0.0, 0.0); (width * height) as usize];
-        Canvas { width, height, pixels }
+        Canvas::filled(width, height, Tuple::color(0.0, 0.0, 0.0))
+    }
+
+    pub fn filled(width: usize, height: usize, color: Tuple) -> Canvas {
+        let pixels = vec![color; width * height];
+        let alpha = vec![1.0; width * height];
+        Canvas { width, height, pixels, alpha }
     }
 
     pub fn pixels_mut(&mut self) -> &mut [Tuple] {
@@ -26,18 +32,139 @@ impl Canvas {
         self.pixels[index]
     }
 
+    // Bresenham line. Endpoints may lie off the canvas; only the pixels that
+    // land on it are written.
+    pub fn draw_line(&mut self, x0: i64, y0: i64, x1: i64, y1: i64, color: Tuple) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+
+        loop {
+            if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+                self.write_pixel(x as usize, y as usize, color);
+            }
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    // Outline of the width x height rectangle with its top-left corner at (x, y).
+    pub fn draw_rect(&mut self, x: i64, y: i64, width: i64, height: i64, color: Tuple) {
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let (right, bottom) = (x + width - 1, y + height - 1);
+        self.draw_line(x, y, right, y, color);
+        self.draw_line(x, bottom, right, bottom, color);
+        self.draw_line(x, y, x, bottom, color);
+        self.draw_line(right, y, right, bottom, color);
+    }
+
+    pub fn write_alpha(&mut self, x: usize, y: usize, alpha: f64) {
+        let index = y * self.width + x;
+        self.alpha[index] = alpha;
+    }
+
+    pub fn alpha_at(&self, x: usize, y: usize) -> f64 {
+        let index = y * self.width + x;
+        self.alpha[index]
+    }
+
+    // Box filter: each factor x factor block averages into one pixel.
+    pub fn downsample(&self, factor: usize) -> Canvas {
+        if factor == 0 || !self.width.is_multiple_of(factor) || !self.height.is_multiple_of(factor) {
+            panic!("Canvas dimensions must be divisible by the downsample factor");
+        }
+
+        let mut canvas = Canvas::new(self.width / factor, self.height / factor);
+        let n = (factor * factor) as f64;
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                let mut color = Tuple::color(0.0, 0.0, 0.0);
+                let mut alpha = 0.0;
+                for dy in 0..factor {
+                    for dx in 0..factor {
+                        color = color + self.pixel_at(x * factor + dx, y * factor + dy);
+                        alpha += self.alpha_at(x * factor + dx, y * factor + dy);
+                    }
+                }
+                canvas.write_pixel(x, y, color / n);
+                canvas.write_alpha(x, y, alpha / n);
+            }
+        }
+        canvas
+    }
+
+    pub fn flip_vertical(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                canvas.write_pixel(x, self.height - 1 - y, self.pixel_at(x, y));
+                canvas.write_alpha(x, self.height - 1 - y, self.alpha_at(x, y));
+            }
+        }
+        canvas
+    }
+
+    pub fn flip_horizontal(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                canvas.write_pixel(self.width - 1 - x, y, self.pixel_at(x, y));
+                canvas.write_alpha(self.width - 1 - x, y, self.alpha_at(x, y));
+            }
+        }
+        canvas
+    }
+
+    pub fn rotate_90(&self, clockwise: bool) -> Canvas {
+        let mut canvas = Canvas::new(self.height, self.width);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if clockwise {
+                    canvas.write_pixel(self.height - 1 - y, x, self.pixel_at(x, y));
+                    canvas.write_alpha(self.height - 1 - y, x, self.alpha_at(x, y));
+                } else {
+                    canvas.write_pixel(y, self.width - 1 - x, self.pixel_at(x, y));
+                    canvas.write_alpha(y, self.width - 1 - x, self.alpha_at(x, y));
+                }
+            }
+        }
+        canvas
+    }
+
     pub fn to_ppm(&self) -> String {
+        self.ppm_with_maxval(255)
+    }
+
+    // Same layout with 16-bit channels, so smooth gradients don't band in
+    // intermediate files.
+    pub fn to_ppm_16bit(&self) -> String {
+        self.ppm_with_maxval(65535)
+    }
+
+    fn ppm_with_maxval(&self, maxval: u16) -> String {
         let mut ppm = String::new();
         ppm.push_str("P3\n");
         ppm.push_str(&format!("{} {}\n", self.width, self.height));
-        ppm.push_str("255\n");
+        ppm.push_str(&format!("{}\n", maxval));
 
         for y in 0..self.height {
             for x in 0..self.width {
-                let pixel = self.pixel_at(x, y);
-                let r = (pixel.0 * 255.0).round().clamp(0.0, 255.0) as u64;
-                let g = (pixel.1 * 255.0).round().clamp(0.0, 255.0) as u64;
-                let b = (pixel.2 * 255.0).round().clamp(0.0, 255.0) as u64;
+                let [r, g, b] = quantize(self.pixel_at(x, y), maxval);
                 ppm.push_str(&format!("{} {} {}\n", r, g, b));
             }
         }
@@ -45,10 +172,44 @@ impl Canvas {
         ppm
     }
 
+    // FNV-1a over the size and the same 8-bit channels to_ppm writes, so it
+    // changes exactly when the saved image would.
+    pub fn checksum(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let size = [self.width as u64, self.height as u64];
+        let pixels = self.pixels.iter().flat_map(|p| quantize(*p, 255).map(|c| c as u8));
+        for byte in size.iter().flat_map(|v| v.to_le_bytes()).chain(pixels) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        hash
+    }
+
     pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        std::fs::create_dir_all("./images")?;
         let filepath = format!("./images/{}", filename);
         std::fs::write(filepath, self.to_ppm())
     }
+
+    // Writes images/basename_0042.ppm for frame 42, so animation frames sort
+    // in order.
+    pub fn save_frame(&self, basename: &str, frame: usize) -> std::io::Result<()> {
+        self.save_frame_padded(basename, frame, 4)
+    }
+
+    pub fn save_frame_padded(&self, basename: &str, frame: usize, width: usize) -> std::io::Result<()> {
+        self.save(&frame_filename(basename, frame, width))
+    }
+}
+
+fn quantize(color: Tuple, maxval: u16) -> [u16; 3] {
+    let max = maxval as f64;
+    let channel = |c: f64| (c * max).round().clamp(0.0, max) as u16;
+    [channel(color.0), channel(color.1), channel(color.2)]
+}
+
+pub fn frame_filename(basename: &str, frame: usize, width: usize) -> String {
+    format!("{}_{:0width$}.ppm", basename, frame, width = width)
 }
 
 #[cfg(test)]
@@ -65,6 +226,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn creating_a_filled_canvas() {
+        let red = Tuple::color(1.0, 0.0, 0.0);
+        let c = Canvas::filled(2, 2, red);
+        assert_eq!(c.pixels.len(), 4);
+        for pixel in c.pixels {
+            assert_eq!(pixel, red);
+        }
+        for alpha in c.alpha {
+            assert_eq!(alpha, 1.0);
+        }
+    }
+
+    fn lit_pixels(c: &Canvas) -> Vec<(usize, usize)> {
+        let mut lit = vec![];
+        for y in 0..c.height {
+            for x in 0..c.width {
+                if c.pixel_at(x, y) != Tuple::color(0.0, 0.0, 0.0) {
+                    lit.push((x, y));
+                }
+            }
+        }
+        lit
+    }
+
+    #[test]
+    fn drawing_a_horizontal_line() {
+        let mut c = Canvas::new(6, 3);
+        c.draw_line(1, 1, 4, 1, Tuple::color(1.0, 0.0, 0.0));
+        assert_eq!(lit_pixels(&c), vec![(1, 1), (2, 1), (3, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn drawing_a_diagonal_line() {
+        let mut c = Canvas::new(5, 5);
+        c.draw_line(4, 4, 0, 0, Tuple::color(1.0, 1.0, 1.0));
+        assert_eq!(lit_pixels(&c), vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+
+        let mut c = Canvas::new(5, 3);
+        c.draw_line(0, 0, 4, 2, Tuple::color(1.0, 1.0, 1.0));
+        assert_eq!(lit_pixels(&c), vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn drawing_off_the_canvas_is_clipped() {
+        let mut c = Canvas::new(4, 4);
+        c.draw_line(-3, 1, 10, 1, Tuple::color(1.0, 1.0, 1.0));
+        c.draw_line(-5, -5, -1, -1, Tuple::color(1.0, 1.0, 1.0));
+        assert_eq!(lit_pixels(&c), vec![(0, 1), (1, 1), (2, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn drawing_a_rectangle_outline() {
+        let mut c = Canvas::new(5, 5);
+        c.draw_rect(1, 1, 3, 3, Tuple::color(1.0, 1.0, 1.0));
+        assert_eq!(lit_pixels(&c), vec![(1, 1), (2, 1), (3, 1), (1, 2), (3, 2), (1, 3), (2, 3), (3, 3)]);
+
+        c.draw_rect(3, 3, 10, 10, Tuple::color(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(4, 3), Tuple::color(1.0, 1.0, 1.0));
+    }
+
     #[test]
     fn writing_pixels_to_a_canvas() {
         let mut c = Canvas::new(10, 20);
@@ -73,6 +295,79 @@ mod tests {
         assert_eq!(c.pixel_at(2, 3), red);
     }
 
+    fn numbered_canvas(width: usize, height: usize) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                c.write_pixel(x, y, Tuple::color(x as f64, y as f64, 0.0));
+            }
+        }
+        c
+    }
+
+    #[test]
+    fn a_new_canvas_is_opaque() {
+        let c = Canvas::new(10, 20);
+        assert!(c.alpha.iter().all(|a| *a == 1.0));
+    }
+
+    #[test]
+    fn flipping_a_canvas_carries_its_alpha() {
+        let mut c = Canvas::new(3, 2);
+        c.write_alpha(0, 0, 0.0);
+        assert_eq!(c.flip_horizontal().alpha_at(2, 0), 0.0);
+        assert_eq!(c.flip_vertical().alpha_at(0, 1), 0.0);
+        assert_eq!(c.rotate_90(true).alpha_at(1, 0), 0.0);
+    }
+
+    #[test]
+    fn downsampling_averages_blocks() {
+        let c = numbered_canvas(4, 4);
+        let small = c.downsample(2);
+        assert_eq!(small.width, 2);
+        assert_eq!(small.height, 2);
+        assert_eq!(small.pixel_at(0, 0), Tuple::color(0.5, 0.5, 0.0));
+        assert_eq!(small.pixel_at(1, 0), Tuple::color(2.5, 0.5, 0.0));
+        assert_eq!(small.pixel_at(0, 1), Tuple::color(0.5, 2.5, 0.0));
+        assert_eq!(small.pixel_at(1, 1), Tuple::color(2.5, 2.5, 0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn downsampling_requires_divisible_dimensions() {
+        Canvas::new(5, 4).downsample(2);
+    }
+
+    #[test]
+    fn flipping_a_canvas_twice_returns_the_original() {
+        let c = numbered_canvas(3, 2);
+        assert_eq!(c.flip_vertical().flip_vertical().pixels, c.pixels);
+        assert_eq!(c.flip_horizontal().flip_horizontal().pixels, c.pixels);
+    }
+
+    #[test]
+    fn flipping_a_canvas_mirrors_its_pixels() {
+        let c = numbered_canvas(3, 2);
+        assert_eq!(c.flip_vertical().pixel_at(0, 0), c.pixel_at(0, 1));
+        assert_eq!(c.flip_horizontal().pixel_at(0, 1), c.pixel_at(2, 1));
+    }
+
+    #[test]
+    fn rotating_a_canvas_swaps_width_and_height() {
+        let c = numbered_canvas(3, 2);
+        let cw = c.rotate_90(true);
+        assert_eq!(cw.width, 2);
+        assert_eq!(cw.height, 3);
+        assert_eq!(cw.pixel_at(1, 0), c.pixel_at(0, 0));
+        assert_eq!(cw.pixel_at(0, 2), c.pixel_at(2, 1));
+
+        let ccw = c.rotate_90(false);
+        assert_eq!(ccw.width, 2);
+        assert_eq!(ccw.height, 3);
+        assert_eq!(ccw.pixel_at(0, 2), c.pixel_at(0, 0));
+        assert_eq!(ccw.rotate_90(true).pixels, c.pixels);
+    }
+
     #[test]
     fn constructing_the_ppm_header() {
         let c = Canvas::new(5, 3);
@@ -125,5 +420,53 @@ mod tests {
         assert_eq!(lines[10], "0 128 0");
         assert_eq!(lines[17], "0 0 255");
     }
-}
 
+    #[test]
+    fn frame_filenames_are_zero_padded() {
+        assert_eq!(frame_filename("anim", 42, 4), "anim_0042.ppm");
+        assert_eq!(frame_filename("anim", 42, 6), "anim_000042.ppm");
+        assert_eq!(frame_filename("anim", 12345, 4), "anim_12345.ppm");
+    }
+
+    #[test]
+    fn saving_a_numbered_frame() {
+        let c = Canvas::new(2, 2);
+        c.save_frame("canvas_frame_test", 42).unwrap();
+        let path = "./images/canvas_frame_test_0042.ppm";
+        let contents = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(contents.starts_with("P3\n2 2\n255\n"));
+    }
+
+    #[test]
+    fn checksums_follow_the_saved_pixels() {
+        let mut a = Canvas::new(4, 3);
+        let mut b = Canvas::new(4, 3);
+        a.write_pixel(1, 2, Tuple::color(0.2, 0.4, 0.6));
+        b.write_pixel(1, 2, Tuple::color(0.2, 0.4, 0.6));
+        assert_eq!(a.checksum(), b.checksum());
+
+        b.write_pixel(3, 0, Tuple::color(0.0, 0.0, 0.01));
+        assert_ne!(a.checksum(), b.checksum());
+
+        // Differences too small to survive quantizing don't count.
+        b.write_pixel(3, 0, Tuple::color(0.0, 0.0, 0.001));
+        assert_eq!(a.checksum(), b.checksum());
+
+        assert_ne!(Canvas::new(4, 3).checksum(), Canvas::new(3, 4).checksum());
+    }
+
+    #[test]
+    fn writing_a_16_bit_ppm() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(0.5, 1.5, -0.5));
+        c.write_pixel(1, 0, Tuple::color(0.0, 1.0, 0.25));
+        let ppm = c.to_ppm_16bit();
+        let lines: Vec<&str> = ppm.lines().collect();
+        assert_eq!(lines[0], "P3");
+        assert_eq!(lines[1], "2 1");
+        assert_eq!(lines[2], "65535");
+        assert_eq!(lines[3], "32768 65535 0");
+        assert_eq!(lines[4], "0 65535 16384");
+    }
+}
@@ -9,6 +9,7 @@ pub trait Colors {
     fn purple() -> Self;
     fn orange() -> Self;
     fn yellow() -> Self;
+    fn from_hex(hex: &str) -> Result<Self, String> where Self: Sized;
 }
 
 impl Colors for Tuple {
@@ -43,4 +44,45 @@ impl Colors for Tuple {
     fn yellow() -> Self {
         Tuple::color(1.0, 1.0, 0.0)
     }
-}
\ No newline at end of file
+
+    // Web colours: #rrggbb or the short #rgb, with or without the #. Each
+    // channel is scaled straight to [0, 1].
+    fn from_hex(hex: &str) -> Result<Self, String> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("invalid hex color: {}", hex));
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).map(|v| v as f64 / 255.0).map_err(|e| e.to_string());
+        match digits.len() {
+            6 => Ok(Tuple::color(channel(&digits[0..2])?, channel(&digits[2..4])?, channel(&digits[4..6])?)),
+            3 => {
+                let double = |i: usize| digits[i..i + 1].repeat(2);
+                Ok(Tuple::color(channel(&double(0))?, channel(&double(1))?, channel(&double(2))?))
+            }
+            _ => Err(format!("invalid hex color: {}", hex)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_from_hex_strings() {
+        assert_eq!(Tuple::from_hex("#ff0000"), Ok(Tuple::red()));
+        assert_eq!(Tuple::from_hex("00ff00"), Ok(Tuple::green()));
+        assert_eq!(Tuple::from_hex("#fff"), Ok(Tuple::white()));
+        let grey = Tuple::from_hex("#808080").unwrap();
+        assert!((grey.0 - 0.5).abs() < 0.01);
+        assert!((grey.1 - 0.5).abs() < 0.01);
+        assert!((grey.2 - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn malformed_hex_strings_are_errors() {
+        for hex in ["", "#", "#ff00", "#ff00000", "#gg0000", "red", "#ff 000"] {
+            assert!(Tuple::from_hex(hex).is_err(), "{} should not parse", hex);
+        }
+    }
+}
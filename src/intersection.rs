@@ -1,5 +1,56 @@
 use crate::{object::Object, ray::Ray, tuple::Tuple};
 
+pub const EPSILON: f64 = 0.0001;
+
+// Intersection error grows with distance, so far-away hits need a larger nudge
+// off the surface. Unit-scale scenes keep the plain bias.
+fn surface_offset(ray: &Ray, t: f64, point: &Tuple, bias: f64) -> f64 {
+    let scale = (t * ray.direction.magnitude())
+        .abs()
+        .max(point.0.abs())
+        .max(point.1.abs())
+        .max(point.2.abs());
+    bias * (scale * 1e-5).max(1.0)
+}
+
+// Refractive indices on either side of hit: n1 is the medium the ray leaves,
+// n2 the one it enters. xs must be the full sorted list the hit came from, so
+// the objects the ray is already inside can be tracked up to the hit.
+pub fn refraction_indices(hit: &Intersection, xs: &[Intersection]) -> (f64, f64) {
+    let mut n1 = 1.0;
+    let mut n2 = 1.0;
+    let mut containers: Vec<&Object> = vec![];
+
+    for i in xs {
+        let is_hit = i.t == hit.t && i.object.id == hit.object.id;
+
+        if is_hit {
+            if containers.is_empty() {
+                n1 = 1.0;
+            } else {
+                n1 = containers.last().unwrap().get_material().refractive_index;
+            }
+        }
+
+        if containers.iter().any(|x| x.id == i.object.id) {
+            containers.retain(|x| x.id != i.object.id);
+        } else {
+            containers.push(i.object);
+        }
+
+        if is_hit {
+            if containers.is_empty() {
+                n2 = 1.0;
+            } else {
+                n2 = containers.last().unwrap().get_material().refractive_index;
+            }
+            break;
+        }
+    }
+
+    (n1, n2)
+}
+
 pub struct Record {
     pub t: f64,
     pub object: Object,
@@ -27,35 +78,11 @@ impl<'a> Intersection<'a> {
     }
 
     pub fn prepare_computations(&self, ray: &Ray, xs: &Vec<Intersection>) -> Record {
-        let mut n1 = 1.0;
-        let mut n2 = 1.0;
-        let mut containers: Vec<Object> = vec![];
-
-        for i in xs {
-            if i == self {
-                if containers.is_empty() {
-                    n1 = 1.0;
-                } else {
-                    n1 = containers.last().unwrap().get_material().refractive_index;
-                }
-            }
-
-            if containers.contains(&i.object) {
-                containers.retain(|x| x != i.object);
-            } else {
-                containers.push(i.object.clone());
-            }
-
-            if i == self {
-                if containers.is_empty() {
-                    n2 = 1.0;
-                } else {
-                    n2 = containers.last().unwrap().get_material().refractive_index;
-                }
-                break;
-            }
-        }
+        self.prepare_computations_with_bias(ray, xs, EPSILON)
+    }
 
+    pub fn prepare_computations_with_bias(&self, ray: &Ray, xs: &Vec<Intersection>, bias: f64) -> Record {
+        let (n1, n2) = refraction_indices(self, xs);
         let mut normalv = self.object.normal_at(&ray.position(self.t));
         let eyev = -ray.direction;
         let inside = if normalv.dot(eyev) < 0.0 {
@@ -65,6 +92,7 @@ impl<'a> Intersection<'a> {
             false
         };
         let point = ray.position(self.t);
+        let offset = surface_offset(ray, self.t, &point, bias);
 
         Record {
             t: self.t,
@@ -74,8 +102,8 @@ impl<'a> Intersection<'a> {
             normalv,
             reflectv: ray.direction.reflect(normalv),
             inside,
-            over_point: point + normalv * 0.0001,
-            under_point: point - normalv * 0.0001,
+            over_point: point + normalv * offset,
+            under_point: point - normalv * offset,
             n1,
             n2,
             schlick: {
@@ -2,6 +2,8 @@ use crate::intersection::Intersection;
 
 pub trait Intersections {
     fn hit(&self) -> Option<&Intersection>;
+    fn count_visible(&self) -> usize;
+    fn sort_by_t(&mut self);
 }
 
 impl<'a> Intersections for Vec<Intersection<'a>> {
@@ -11,12 +13,26 @@ impl<'a> Intersections for Vec<Intersection<'a>> {
             .min_by(|a, b| a.t.partial_cmp(&b.t)
             .unwrap_or(std::cmp::Ordering::Greater))
     }
+
+    fn count_visible(&self) -> usize {
+        self.iter().filter(|i| i.t >= 0.0).count()
+    }
+
+    // Stable, and NaN hits from degenerate transforms go last instead of panicking.
+    fn sort_by_t(&mut self) {
+        self.sort_by(|a, b| match (a.t.is_nan(), b.t.is_nan()) {
+            (false, false) => a.t.total_cmp(&b.t),
+            (true, true) => std::cmp::Ordering::Equal,
+            (true, false) => std::cmp::Ordering::Greater,
+            (false, true) => std::cmp::Ordering::Less,
+        });
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::vec;
-    use crate::{helper::glass_sphere, matrix::Matrix, object::Object, ray::Ray, transformation::Transformation, tuple::Tuple};
+    use crate::{helper::glass_sphere, intersection::refraction_indices, material::Material, light::Light, matrix::Matrix, object::Object, ray::Ray, transformation::Transformation, tuple::Tuple, world::World};
 
     use super::*;
 
@@ -81,6 +97,18 @@ mod tests {
         assert_eq!(i.map(|i| i.t), Some(i4.t));
     }
 
+    #[test]
+    fn counting_the_visible_intersections() {
+        let s = Object::sphere();
+        let xs = vec![
+            Intersection::new(5.0, &s),
+            Intersection::new(7.0, &s),
+            Intersection::new(-3.0, &s),
+            Intersection::new(2.0, &s),
+        ];
+        assert_eq!(xs.count_visible(), 3);
+    }
+
     #[test]
     fn precomputing_the_state_of_an_intersection() {
         let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
@@ -125,6 +153,38 @@ mod tests {
         assert!(comps.point.2 > comps.over_point.2);
     }
 
+    #[test]
+    fn the_offset_is_unchanged_for_unit_scale_scenes() {
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let shape = Object::sphere();
+        let i = Intersection::new(4.0, &shape);
+        let comps = i.prepare_computations(&r, &vec![]);
+        assert!(((comps.point - comps.over_point).magnitude() - 0.0001).abs() < 1e-12);
+        assert!(((comps.under_point - comps.point).magnitude() - 0.0001).abs() < 1e-12);
+    }
+
+    #[test]
+    fn distant_spheres_do_not_shadow_themselves() {
+        for (distance, radius) in [(1e4, 1.0), (1e10, 1e4)] {
+            let s = Object::sphere()
+                .with_transform(Matrix::translation(0.0, 0.0, distance) * Matrix::scaling(radius, radius, radius));
+            let light = Light::new(Tuple::point(0.0, distance * 0.5, 0.0), Tuple::color(1.0, 1.0, 1.0));
+            let world = World::new().with_objects(vec![s]).with_lights(vec![light]);
+            for k in 0..200 {
+                let y = (k as f64 / 200.0 - 0.5) * radius * 1.2 / distance;
+                let r = Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, y, 1.0).normalize());
+                let xs = world.intersect(&r);
+                if let Some(hit) = xs.hit() {
+                    let comps = hit.prepare_computations(&r, &xs);
+                    let lightv = (light.position() - comps.point).normalize();
+                    if comps.normalv.dot(lightv) > 0.0 {
+                        assert!(!world.is_shadowed(&comps.over_point));
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn precomputing_the_reflection_vector() {
         let shape = Object::plane();
@@ -136,17 +196,17 @@ mod tests {
 
     #[test]
     fn finding_n1_and_n2_at_various_intersections() {
-        let mut a = glass_sphere()
+        let a = glass_sphere()
             .with_transform(Matrix::scaling(2.0, 2.0, 2.0));
-        a.set_material(a.get_material().clone().with_refractive_index(1.5));
+        a.update_material(|m| m.refractive_index = 1.5);
 
-        let mut b = glass_sphere()
+        let b = glass_sphere()
             .with_transform(Matrix::translation(0.0, 0.0, -0.25));
-        b.set_material(b.get_material().clone().with_refractive_index(2.0));
+        b.update_material(|m| m.refractive_index = 2.0);
 
-        let mut c = glass_sphere()
+        let c = glass_sphere()
             .with_transform(Matrix::translation(0.0, 0.0, 0.25));
-        c.set_material(c.get_material().clone().with_refractive_index(2.5));
+        c.update_material(|m| m.refractive_index = 2.5);
 
         let r = Ray::new(Tuple::point(0.0, 0.0, -4.0), Tuple::vector(0.0, 0.0, 1.0));
         let xs = vec![
@@ -183,6 +243,29 @@ mod tests {
         assert_eq!(comps.n2, 1.0);
     }
 
+    #[test]
+    fn n1_and_n2_track_distinct_objects_that_are_equal_in_value() {
+        let a = glass_sphere();
+        let b = glass_sphere();
+        assert_eq!(a, b);
+
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = vec![
+            Intersection::new(4.0, &a),
+            Intersection::new(4.0, &b),
+            Intersection::new(6.0, &a),
+            Intersection::new(6.0, &b),
+        ];
+
+        let comps = xs[1].prepare_computations(&r, &xs);
+        assert_eq!(comps.n1, 1.5);
+        assert_eq!(comps.n2, 1.5);
+
+        let comps = xs[3].prepare_computations(&r, &xs);
+        assert_eq!(comps.n1, 1.5);
+        assert_eq!(comps.n2, 1.0);
+    }
+
     #[test]
     fn the_under_point_is_offset_below_the_surface() {
         let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
@@ -233,4 +316,61 @@ mod tests {
         let alpha = 1e-4;
         assert!((reflectance - 0.48873).abs() < alpha);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn sorting_intersections_with_a_nan_t_does_not_panic() {
+        let s = Object::sphere();
+        let mut xs = vec![
+            Intersection::new(2.0, &s),
+            Intersection::new(f64::NAN, &s),
+            Intersection::new(-1.0, &s),
+        ];
+        xs.sort_by_t();
+        assert_eq!(xs[0].t, -1.0);
+        assert_eq!(xs[1].t, 2.0);
+        assert!(xs[2].t.is_nan());
+    }
+
+    #[test]
+    fn sorting_intersections_keeps_equal_t_in_insertion_order() {
+        let a = Object::sphere();
+        let b = Object::plane();
+        let c = Object::cube();
+        let mut xs = vec![
+            Intersection::new(3.0, &c),
+            Intersection::new(1.0, &a),
+            Intersection::new(1.0, &b),
+            Intersection::new(1.0, &c),
+        ];
+        xs.sort_by_t();
+        assert_eq!(xs[0].object.id, a.id);
+        assert_eq!(xs[1].object.id, b.id);
+        assert_eq!(xs[2].object.id, c.id);
+        assert_eq!(xs[3].t, 3.0);
+    }
+
+    #[test]
+    fn refraction_indices_for_the_nested_glass_spheres() {
+        let a = glass_sphere()
+            .with_transform(Matrix::scaling(2.0, 2.0, 2.0))
+            .with_material(Material::glass().with_refractive_index(1.5));
+        let b = glass_sphere()
+            .with_transform(Matrix::translation(0.0, 0.0, -0.25))
+            .with_material(Material::glass().with_refractive_index(2.0));
+        let c = glass_sphere()
+            .with_transform(Matrix::translation(0.0, 0.0, 0.25))
+            .with_material(Material::glass().with_refractive_index(2.5));
+        let xs = vec![
+            Intersection::new(2.0, &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6.0, &a),
+        ];
+        let expected = [(1.0, 1.5), (1.5, 2.0), (2.0, 2.5), (2.5, 2.5), (2.5, 1.5), (1.5, 1.0)];
+        for (hit, n) in xs.iter().zip(expected) {
+            assert_eq!(refraction_indices(hit, &xs), n);
+        }
+    }
+}
@@ -16,4 +16,9 @@ pub mod shape;
 pub mod patterns;
 pub mod pattern;
 pub mod helper;
-pub mod group;
\ No newline at end of file
+pub mod refractive_index;
+
+pub mod sequence;
+pub mod render_stats;
+pub mod bounds;
+pub mod axis;
@@ -1,14 +1,92 @@
-use crate::tuple::{Point, Color};
+use crate::sequence::{CenteredSequence, Sequence};
+use crate::tuple::{Point, Color, Tuple};
+
+const SOFT_SAMPLES: usize = 16;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Light {
     position: Point,
     intensity: Color,
+    softness: f64,
+    falloff: (f64, f64, f64),
 }
 
 impl Light {
     pub fn new(position: Point, intensity: Color) -> Light {
-        Light { position, intensity }
+        Light { position, intensity, softness: 0.0, falloff: (1.0, 0.0, 0.0) }
+    }
+
+    // Blackbody colour from Tanner Helland's fit to the Planckian locus,
+    // good for roughly 1000K to 40000K.
+    pub fn from_temperature(position: Point, kelvin: f64, brightness: f64) -> Light {
+        let temp = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+        let red = if temp <= 66.0 {
+            255.0
+        } else {
+            329.698727446 * (temp - 60.0).powf(-0.1332047592)
+        };
+        let green = if temp <= 66.0 {
+            99.4708025861 * temp.ln() - 161.1195681661
+        } else {
+            288.1221695283 * (temp - 60.0).powf(-0.0755148492)
+        };
+        let blue = if temp >= 66.0 {
+            255.0
+        } else if temp <= 19.0 {
+            0.0
+        } else {
+            138.5177312231 * (temp - 10.0).ln() - 305.0447927307
+        };
+
+        let channel = |c: f64| c.clamp(0.0, 255.0) / 255.0 * brightness;
+        Light::new(position, Tuple::color(channel(red), channel(green), channel(blue)))
+    }
+
+    pub fn with_softness(mut self, radius: f64) -> Light {
+        self.softness = radius;
+        self
+    }
+
+    pub fn softness(&self) -> f64 {
+        self.softness
+    }
+
+    // Constant, linear and quadratic attenuation terms. The default (1, 0, 0)
+    // is no falloff at all.
+    pub fn with_falloff(mut self, constant: f64, linear: f64, quadratic: f64) -> Light {
+        self.falloff = (constant, linear, quadratic);
+        self
+    }
+
+    pub fn falloff_at(&self, distance: f64) -> f64 {
+        let (constant, linear, quadratic) = self.falloff;
+        1.0 / (constant + linear * distance + quadratic * distance * distance)
+    }
+
+    pub fn sample_points(&self) -> Vec<Point> {
+        self.sample_points_with(&mut CenteredSequence)
+    }
+
+    // Shadow rays aim at these points. A soft light spreads them through a ball
+    // of the softness radius on a spiral; the sequence jitters each point within
+    // its stratum, and a centered sequence gives the plain spiral.
+    pub fn sample_points_with(&self, seq: &mut dyn Sequence) -> Vec<Point> {
+        if self.softness <= 0.0 {
+            return vec![self.position];
+        }
+
+        let golden_angle = std::f64::consts::PI * (3.0 - 5.0_f64.sqrt());
+        (0..SOFT_SAMPLES)
+            .map(|i| {
+                let f = (i as f64 + seq.next()) / SOFT_SAMPLES as f64;
+                let y = 1.0 - 2.0 * f;
+                let ring = (1.0 - y * y).sqrt();
+                let theta = golden_angle * i as f64 + 2.0 * std::f64::consts::PI * (seq.next() - 0.5);
+                let r = self.softness * f.cbrt();
+                self.position + Tuple::vector(ring * theta.cos(), y, ring * theta.sin()) * r
+            })
+            .collect()
     }
 
     pub fn position(&self) -> Point {
@@ -34,4 +112,40 @@ mod tests {
         assert_eq!(light.position(), position);
         assert_eq!(light.intensity(), intensity);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn a_hard_light_samples_only_its_position() {
+        let light = Light::new(Tuple::point(1.0, 2.0, 3.0), Tuple::color(1.0, 1.0, 1.0));
+        assert_eq!(light.softness(), 0.0);
+        assert_eq!(light.sample_points(), vec![Tuple::point(1.0, 2.0, 3.0)]);
+    }
+
+    #[test]
+    fn a_soft_light_samples_within_its_radius() {
+        let position = Tuple::point(1.0, 2.0, 3.0);
+        let light = Light::new(position, Tuple::color(1.0, 1.0, 1.0)).with_softness(0.5);
+        let samples = light.sample_points();
+        assert_eq!(samples.len(), SOFT_SAMPLES);
+        for p in &samples {
+            assert!((*p - position).magnitude() <= 0.5 + 1e-9);
+        }
+        assert_eq!(samples, light.sample_points());
+    }
+
+    #[test]
+    fn a_6500k_light_is_roughly_white() {
+        let light = Light::from_temperature(Tuple::point(0.0, 0.0, 0.0), 6500.0, 1.0);
+        let c = light.intensity();
+        assert!((c.0 - 1.0).abs() < 0.05);
+        assert!((c.1 - 1.0).abs() < 0.05);
+        assert!((c.2 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn a_3000k_light_is_warm() {
+        let light = Light::from_temperature(Tuple::point(0.0, 0.0, 0.0), 3000.0, 2.0);
+        let c = light.intensity();
+        assert!(c.0 > c.2);
+        assert_eq!(c.0, 2.0);
+    }
+}
@@ -1,9 +1,16 @@
+use std::sync::{Arc, RwLock};
+
 use crate::light::Light;
 use crate::object::Object;
 use crate::pattern::Pattern;
+use crate::refractive_index::RefractiveIndex;
 use crate::tuple::{Tuple, Color};
 use crate::color::Colors;
 
+// One material behind several objects, e.g. every triangle of a mesh or every
+// copy of an instanced shape. Edits through the lock reach all of them.
+pub type SharedMaterial = Arc<RwLock<Material>>;
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Material {
     pub color: Color,
@@ -12,9 +19,15 @@ pub struct Material {
     pub specular: f64,
     pub shininess: f64,
     pub reflectivity: f64,
+    pub fresnel_reflectivity: bool,
     pub transparency: f64,
+    pub opacity: f64,
+    pub max_bounces: Option<usize>,
     pub refractive_index: f64,
+    pub thin: bool,
     pub pattern: Option<Pattern>,
+    pub ambient_color: Option<Color>,
+    pub emissive: Color,
 }
 
 impl Material {
@@ -26,12 +39,50 @@ impl Material {
             specular: 0.9,
             shininess: 200.0,
             reflectivity: 0.0,
+            fresnel_reflectivity: false,
             transparency: 0.0,
+            opacity: 1.0,
+            max_bounces: None,
             refractive_index: 1.0,
+            thin: false,
             pattern: None,
+            ambient_color: None,
+            emissive: Color::black(),
         }
     }
 
+    pub fn shared(self) -> SharedMaterial {
+        Arc::new(RwLock::new(self))
+    }
+
+    pub fn unlit(color: Color) -> Material {
+        Material::new()
+            .with_color(color)
+            .with_ambient(1.0)
+            .with_diffuse(0.0)
+            .with_specular(0.0)
+    }
+
+    pub fn glass() -> Material {
+        Material::new()
+            .with_color(Color::color(1.0, 1.0, 1.0))
+            .with_ambient(0.0)
+            .with_diffuse(0.3)
+            .with_specular(0.7)
+            .with_shininess(200.0)
+            .with_reflectivity(0.5)
+            .with_transparency(1.0)
+            .with_refractive_index(RefractiveIndex::GLASS)
+    }
+
+    pub fn water() -> Material {
+        Material::glass().with_refractive_index(RefractiveIndex::WATER)
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Material, String> {
+        Ok(Material::new().with_color(Color::from_hex(hex)?))
+    }
+
     pub fn with_color(mut self, color: Color) -> Self {
         self.color = color;
         self
@@ -62,6 +113,35 @@ impl Material {
         self
     }
 
+    // Plain see-through for fades and cutouts; unlike transparency the ray
+    // carries on straight instead of refracting.
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    // Caps how many more reflection/refraction bounces rays leaving this
+    // surface may take. It can only lower the world's depth, never raise it.
+    pub fn with_max_bounces(mut self, max_bounces: usize) -> Self {
+        self.max_bounces = Some(max_bounces);
+        self
+    }
+
+    pub fn with_fresnel_reflectivity(mut self, fresnel_reflectivity: bool) -> Self {
+        self.fresnel_reflectivity = fresnel_reflectivity;
+        self
+    }
+
+    // With fresnel_reflectivity on, reflectivity is the value at normal
+    // incidence and rises towards 1.0 at grazing angles along the Schlick curve.
+    pub fn reflectivity_at(&self, schlick: f64) -> f64 {
+        if self.fresnel_reflectivity {
+            self.reflectivity + (1.0 - self.reflectivity) * schlick
+        } else {
+            self.reflectivity
+        }
+    }
+
     pub fn with_transparency(mut self, transparency: f64) -> Self {
         self.transparency = transparency;
         self
@@ -72,22 +152,67 @@ impl Material {
         self
     }
 
+    // A zero-thickness shell, like a soap bubble: light passes through
+    // without bending, since going in and coming out cancel.
+    pub fn with_thin(mut self, thin: bool) -> Self {
+        self.thin = thin;
+        self
+    }
+
     pub fn with_pattern(mut self, pattern: Pattern) -> Self {
         self.pattern = Some(pattern);
         self
     }
 
+    pub fn with_ambient_color(mut self, ambient_color: Color) -> Self {
+        self.ambient_color = Some(ambient_color);
+        self
+    }
+
+    pub fn with_emissive(mut self, emissive: Color) -> Self {
+        self.emissive = emissive;
+        self
+    }
+
     pub fn lighting(&self, object: &Object, light: &Light, position: Tuple, eyev: Tuple, normalv: Tuple, in_shadow: bool) -> Color {
+        let intensity = if in_shadow { 0.0 } else { 1.0 };
+        self.lighting_with_intensity(object, light, position, eyev, normalv, intensity)
+    }
+
+    pub fn lighting_with_intensity(&self, object: &Object, light: &Light, position: Tuple, eyev: Tuple, normalv: Tuple, intensity: f64) -> Color {
+        let (ambient, diffuse, specular) = self.lighting_components(object, light, position, eyev, normalv, intensity);
+        ambient + diffuse + specular
+    }
+
+    // Ambient for the given total light arriving at the surface. World shading
+    // adds this once per hit rather than once per light.
+    pub fn ambient_at(&self, object: &Object, position: Tuple, light_intensity: Color) -> Color {
+        self.ambient_term(self.surface_color(object, position), light_intensity)
+    }
 
-        let color = if let Some(pattern) = &self.pattern {
-            pattern.pattern_at_shape(&object, position)
+    fn surface_color(&self, object: &Object, position: Tuple) -> Color {
+        if let Some(pattern) = &self.pattern {
+            pattern.pattern_at_shape(object, position)
         } else {
             self.color
-        };
+        }
+    }
+
+    fn ambient_term(&self, color: Color, light_intensity: Color) -> Color {
+        match self.ambient_color {
+            Some(ambient_color) => ambient_color * light_intensity * self.ambient,
+            None => color * light_intensity * self.ambient,
+        }
+    }
 
+    // The ambient, diffuse and specular terms of lighting, kept apart so they
+    // can be weighted or inspected individually. Diffuse and specular are
+    // already scaled by intensity.
+    pub fn lighting_components(&self, object: &Object, light: &Light, position: Tuple, eyev: Tuple, normalv: Tuple, intensity: f64) -> (Color, Color, Color) {
+        let color = self.surface_color(object, position);
         let effective_color = color * light.intensity();
         let lightv = (light.position() - position).normalize();
-        let ambient = effective_color * self.ambient;
+        let ambient = self.ambient_term(color, light.intensity());
         let light_dot_normal = lightv.dot(normalv);
 
         let (diffuse, specular) = if light_dot_normal < 0.0 {
@@ -100,26 +225,38 @@ impl Material {
             if reflect_dot_eye <= 0.0 {
                 (diffuse, Color::black())
             } else {
-                let factor = reflect_dot_eye.powf(self.shininess);
+                let factor = specular_factor(reflect_dot_eye, self.shininess);
                 let specular = light.intensity() * self.specular * factor;
                 (diffuse, specular)
             }
         };
 
-        if in_shadow {
-            ambient
-        } else {
-            ambient + diffuse + specular
-        }
+        (ambient, diffuse * intensity, specular * intensity)
     }
 }
 
+// Rounding can push reflect_dot_eye just past 1.0, which a high shininess
+// blows up into a speckle, and a shininess of zero or less turns the highlight
+// into a flat wash.
+fn specular_factor(reflect_dot_eye: f64, shininess: f64) -> f64 {
+    let shininess = if shininess.is_nan() || shininess <= 0.0 { 1.0 } else { shininess };
+    reflect_dot_eye.clamp(0.0, 1.0).powf(shininess)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::tuple::Tuple;
     use crate::light::Light;
 
+    #[test]
+    fn a_material_from_a_hex_color() {
+        let m = Material::from_hex("#ff8000").unwrap();
+        assert_eq!(m.color, Tuple::color(1.0, 128.0 / 255.0, 0.0));
+        assert_eq!(m.ambient, Material::new().ambient);
+        assert!(Material::from_hex("#zz8000").is_err());
+    }
+
     #[test]
     fn the_default_material() {
         let m = Material::new();
@@ -131,6 +268,52 @@ mod tests {
         assert_eq!(m.shininess, 200.0);
     }
 
+    #[test]
+    fn an_unlit_material_ignores_light_direction_and_shadow() {
+        let m = Material::unlit(Color::red());
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        for light_position in [
+            Tuple::point(0.0, 0.0, -10.0),
+            Tuple::point(0.0, 10.0, -10.0),
+            Tuple::point(0.0, 0.0, 10.0),
+        ] {
+            let light = Light::new(light_position, Tuple::color(1.0, 1.0, 1.0));
+            for in_shadow in [false, true] {
+                let result = m.lighting(&Object::test_shape(), &light, position, eyev, normalv, in_shadow);
+                assert_eq!(result, Color::red());
+            }
+        }
+    }
+
+    #[test]
+    fn the_glass_material() {
+        let m = Material::glass();
+        assert_eq!(m.transparency, 1.0);
+        assert_eq!(m.refractive_index, RefractiveIndex::GLASS);
+        assert_eq!(Material::water().refractive_index, RefractiveIndex::WATER);
+    }
+
+    #[test]
+    fn lighting_the_glass_material_matches_the_manual_builder() {
+        let manual = Material::new()
+            .with_ambient(0.0)
+            .with_diffuse(0.3)
+            .with_specular(0.7)
+            .with_reflectivity(0.5)
+            .with_transparency(1.0)
+            .with_refractive_index(1.52);
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = Light::new(Tuple::point(0.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let glass = Material::glass().lighting(&Object::test_shape(), &light, position, eyev, normalv, false);
+        let expected = manual.lighting(&Object::test_shape(), &light, position, eyev, normalv, false);
+
+        assert_eq!(glass, expected);
+    }
+
     #[test]
     fn lighting_with_the_eye_between_the_light_and_the_surface() {
         let m = Material::new();
@@ -170,6 +353,28 @@ mod tests {
         assert!((result.2 - 0.7364).abs() < alpha);
     }
 
+    #[test]
+    fn a_colored_light_tints_the_highlight_and_filters_the_diffuse() {
+        let eyev = Tuple::vector(0.0, -2_f64.sqrt() / 2.0, -2_f64.sqrt() / 2.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = Light::new(Tuple::point(0.0, 10.0, -10.0), Tuple::color(1.0, 0.0, 0.0));
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let alpha = 1e-4;
+
+        let white = Material::new();
+        let (ambient, diffuse, specular) = white.lighting_components(&Object::test_shape(), &light, position, eyev, normalv, 1.0);
+        assert_eq!(ambient, Tuple::color(0.1, 0.0, 0.0));
+        assert!((diffuse.0 - 0.6364).abs() < alpha && diffuse.1 == 0.0 && diffuse.2 == 0.0);
+        assert!((specular.0 - 0.9).abs() < alpha && specular.1 == 0.0 && specular.2 == 0.0);
+
+        // The diffuse goes through the surface colour; the highlight is the
+        // light's own colour, whatever the surface.
+        let bluish = Material::new().with_color(Tuple::color(0.5, 0.5, 1.0));
+        let (_, diffuse, specular) = bluish.lighting_components(&Object::test_shape(), &light, position, eyev, normalv, 1.0);
+        assert!((diffuse.0 - 0.3182).abs() < alpha && diffuse.1 == 0.0 && diffuse.2 == 0.0);
+        assert!((specular.0 - 0.9).abs() < alpha && specular.1 == 0.0 && specular.2 == 0.0);
+    }
+
     #[test]
     fn lighting_with_eye_in_the_path_of_the_reflection_vector() {
         let m = Material::new();
@@ -209,6 +414,33 @@ mod tests {
         assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
     }
 
+    #[test]
+    fn lighting_in_shadow_uses_the_ambient_color() {
+        let m = Material::new()
+            .with_color(Tuple::color(1.0, 0.0, 0.0))
+            .with_ambient_color(Tuple::color(0.0, 0.0, 1.0));
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let result = m.lighting(&Object::test_shape(), &light, position, eyev, normalv, true);
+        assert_eq!(result, Tuple::color(0.0, 0.0, 0.1));
+    }
+
+    #[test]
+    fn lighting_with_an_ambient_color_keeps_the_diffuse_color() {
+        let m = Material::new()
+            .with_color(Tuple::color(1.0, 0.0, 0.0))
+            .with_ambient_color(Tuple::color(0.0, 0.0, 1.0))
+            .with_specular(0.0);
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let result = m.lighting(&Object::test_shape(), &light, position, eyev, normalv, false);
+        assert_eq!(result, Tuple::color(0.9, 0.0, 0.1));
+    }
+
     #[test]
     fn reflectivity_for_the_default_material() {
         let m = Material::new();
@@ -221,4 +453,76 @@ mod tests {
         assert_eq!(m.transparency, 0.0);
         assert_eq!(m.refractive_index, 1.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn specular_factor_clamps_reflect_dot_eye_above_one() {
+        let factor = specular_factor(1.0000001, 1e9);
+        assert_eq!(factor, 1.0);
+        assert_eq!(specular_factor(0.5, 10.0), 0.5_f64.powf(10.0));
+    }
+
+    #[test]
+    fn specular_factor_guards_against_bad_shininess() {
+        assert_eq!(specular_factor(0.5, 0.0), 0.5);
+        assert_eq!(specular_factor(0.5, f64::NAN), 0.5);
+        assert_eq!(specular_factor(0.5, -3.0), 0.5);
+        assert_eq!(specular_factor(0.25, 0.5), 0.5);
+    }
+
+    #[test]
+    fn lighting_with_a_slightly_overlong_eye_vector_stays_finite() {
+        let m = Material::new().with_shininess(1e9);
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0000001);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let result = m.lighting(&Object::sphere(), &light, position, eyev, normalv, false);
+        assert!(!result.0.is_nan());
+        assert!((result.0 - 1.9).abs() < 1e-10);
+    }
+
+    #[test]
+    fn lighting_components_sum_to_lighting() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let s = 2.0_f64.sqrt() / 2.0;
+        let fixtures = [
+            (Tuple::vector(0.0, 0.0, -1.0), Tuple::point(0.0, 0.0, -10.0)),
+            (Tuple::vector(0.0, s, -s), Tuple::point(0.0, 0.0, -10.0)),
+            (Tuple::vector(0.0, 0.0, -1.0), Tuple::point(0.0, 10.0, -10.0)),
+            (Tuple::vector(0.0, -s, -s), Tuple::point(0.0, 10.0, -10.0)),
+            (Tuple::vector(0.0, 0.0, -1.0), Tuple::point(0.0, 0.0, 10.0)),
+        ];
+        for (eyev, light_position) in fixtures {
+            let light = Light::new(light_position, Tuple::color(1.0, 1.0, 1.0));
+            for in_shadow in [false, true] {
+                let intensity = if in_shadow { 0.0 } else { 1.0 };
+                let (a, d, sp) = m.lighting_components(&Object::sphere(), &light, position, eyev, normalv, intensity);
+                let result = m.lighting(&Object::sphere(), &light, position, eyev, normalv, in_shadow);
+                assert_eq!(a + d + sp, result);
+            }
+        }
+    }
+
+    #[test]
+    fn lighting_components_with_the_eye_between_light_and_surface() {
+        let m = Material::new();
+        let light = Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let (a, d, s) = m.lighting_components(&Object::sphere(), &light, Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, -1.0), Tuple::vector(0.0, 0.0, -1.0), 1.0);
+        assert_eq!(a, Tuple::color(0.1, 0.1, 0.1));
+        assert_eq!(d, Tuple::color(0.9, 0.9, 0.9));
+        assert_eq!(s, Tuple::color(0.9, 0.9, 0.9));
+    }
+
+    #[test]
+    fn fresnel_reflectivity_rises_towards_grazing_angles() {
+        let m = Material::new().with_reflectivity(0.2).with_fresnel_reflectivity(true);
+        assert_eq!(m.reflectivity_at(0.0), 0.2);
+        assert!((m.reflectivity_at(0.04) - 0.232).abs() < 1e-10);
+        assert_eq!(m.reflectivity_at(1.0), 1.0);
+
+        let flat = Material::new().with_reflectivity(0.2);
+        assert_eq!(flat.reflectivity_at(1.0), 0.2);
+    }
+}
@@ -15,6 +15,10 @@ impl Matrix {
         Matrix { size, data }
     }
 
+    pub fn as_slice(&self) -> &[f64] {
+        &self.data
+    }
+
     pub fn identity() -> Matrix {
         Matrix::new(vec![
             1.0, 0.0, 0.0, 0.0,
@@ -116,6 +120,21 @@ impl Matrix {
         }
     }
 
+    // Exponentiation by squaring, so pow(n) takes O(log n) multiplications.
+    pub fn pow(&self, n: u32) -> Matrix {
+        let mut result = Matrix::identity();
+        let mut base = self.clone();
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            n >>= 1;
+        }
+        result
+    }
+
     pub fn is_invertible(&self) -> bool {
         self.determinant() != 0.0
     }
@@ -252,6 +271,18 @@ impl std::ops::Mul<Tuple> for Matrix {
     }
 }
 
+impl TryFrom<&[f64]> for Matrix {
+    type Error = String;
+
+    fn try_from(data: &[f64]) -> Result<Self, Self::Error> {
+        let size = (data.len() as f64).sqrt() as usize;
+        if data.is_empty() || size * size != data.len() {
+            return Err(format!("Matrix data must be square, got {} values", data.len()));
+        }
+        Ok(Matrix { size, data: data.to_vec() })
+    }
+}
+
 #[cfg(test)]
 mod tests{
     use super::*;
@@ -656,4 +687,39 @@ mod tests{
         }
     }
 
+
+    #[test]
+    fn round_tripping_a_matrix_through_a_slice() {
+        let m = Matrix::new(vec![
+            1.0, 2.0, 3.0, 4.0,
+            5.5, 6.5, 7.5, 8.5,
+            9.0, 10.0, 11.0, 12.0,
+            13.5, 14.5, 15.5, 16.5,
+        ]);
+        let copy = Matrix::try_from(m.as_slice()).unwrap();
+        assert_eq!(copy, m);
+        assert_eq!(copy.size, 4);
+    }
+
+    #[test]
+    fn a_matrix_cannot_be_built_from_a_non_square_slice() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert!(Matrix::try_from(&data[..]).is_err());
+        assert!(Matrix::try_from(&[][..]).is_err());
+    }
+
+    #[test]
+    fn raising_a_matrix_to_a_power() {
+        use crate::transformation::Transformation;
+
+        let r = Matrix::rotation_z(std::f64::consts::PI / 6.0);
+        assert_eq!(r.pow(0), Matrix::identity());
+        assert_eq!(r.pow(1), r);
+        assert_eq!(r.pow(3), &(&r * &r) * &r);
+
+        let full_turn = r.pow(12);
+        for (a, b) in full_turn.data.iter().zip(Matrix::identity().data.iter()) {
+            assert!((a - b).abs() < 1e-9);
+        }
+    }
 }
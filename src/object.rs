@@ -1,16 +1,38 @@
-use crate::{intersection::Intersection, material::Material, matrix::Matrix, ray::Ray, shape::Shape, shapes::{cone::Cone, cube::Cube, cylinder::Cylinder, plane::Plane, sphere::Sphere, test_shape::TestShape}, tuple::{Point, Vector}};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLockReadGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[derive(Clone, PartialEq, Debug)]
+use crate::{bounds::Bounds, intersection::{Intersection, EPSILON}, material::{Material, SharedMaterial}, matrix::Matrix, patterns::mapping::PatternMapping, ray::Ray, shape::Shape, shapes::{cone::Cone, cube::Cube, cylinder::Cylinder, group::Group, instance::Instance, plane::Plane, sphere::Sphere, test_shape::TestShape}, tuple::{Point, Tuple, Vector}};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+static NO_CHILDREN: Vec<Object> = Vec::new();
+
+fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Clone, Debug)]
 pub struct Object {
-    pub shape: Shape,
-    pub transform: Matrix,
-    pub material: Material,
+    pub id: usize,
+    // Private so a group's children can't be swapped in without their parent
+    // space; see children_mut.
+    shape: Shape,
+    pub material: SharedMaterial,
     pub parent: Option<usize>,
+    pub casts_shadow: bool,
+    pub layer: u32,
+    // Always set through set_transform, which refreshes the cached inverses
+    // below.
+    transform: Matrix,
+    inverse: Matrix,
+    // World space to the parent group's space: the combined inverse of every
+    // enclosing group, kept up to date as children are added and groups move.
+    parent_inverse: Matrix,
 }
 
 impl Object {
     pub fn new(shape: Shape) -> Object {
-        Object { shape, transform: Matrix::identity(), material: Material::new(), parent: None }
+        Object { id: next_id(), shape, transform: Matrix::identity(), material: Material::new().shared(), parent: None, casts_shadow: true, layer: 0, inverse: Matrix::identity(), parent_inverse: Matrix::identity() }
     }
 
     pub fn test_shape() -> Object {
@@ -37,25 +59,202 @@ impl Object {
         Object::new(Shape::Cone(Cone::new()))
     }
 
-    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
-        let itx = self.shape.local_intersect(&ray.transform(&self.transform.inverse()));
-        itx.into_iter().map(|t| Intersection::new(t, self)).collect()
+    pub fn group() -> Object {
+        Object::new(Shape::Group(Group::new()))
+    }
+
+    // Places shared geometry without copying it; see Instance. Copies start
+    // out on the geometry's own material, shared rather than copied.
+    pub fn instance(geometry: Arc<Object>) -> Object {
+        assert!(!matches!(geometry.get_shape(), Shape::Group(_)), "Instanced geometry must be a single shape, not a group");
+        let material = Arc::clone(&geometry.material);
+        Object::new(Shape::Instance(Instance::new(geometry))).with_shared_material(material)
+    }
+
+    pub fn get_shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn get_children(&self) -> &Vec<Object> {
+        match &self.shape {
+            Shape::Group(group) => group.get_children(),
+            _ => &NO_CHILDREN,
+        }
+    }
+
+    pub fn child_count(&self) -> usize {
+        self.get_children().len()
+    }
+
+    // Everything beneath this object, nested groups included (and counted).
+    pub fn descendant_count(&self) -> usize {
+        self.get_children().iter().map(|child| 1 + child.descendant_count()).sum()
+    }
+
+    // Children can be edited, added or removed freely through the returned
+    // handle; the group's space is pushed down to all of them when it drops.
+    pub fn children_mut(&mut self) -> Option<ChildrenMut<'_>> {
+        match self.shape {
+            Shape::Group(_) => Some(ChildrenMut { group: self }),
+            _ => None,
+        }
+    }
+
+    fn group_children_mut(&mut self) -> Option<&mut Vec<Object>> {
+        match &mut self.shape {
+            Shape::Group(group) => Some(group.children_mut()),
+            _ => None,
+        }
+    }
+
+    // Only groups have children; anything else is a scene-building mistake.
+    pub fn add_child(&mut self, mut child: Object) {
+        child.set_parent_inverse(self.world_inverse());
+        match self.group_children_mut() {
+            Some(children) => children.push(child),
+            None => panic!("Only groups can have children"),
+        }
+    }
+
+    pub fn add_children(&mut self, children: Vec<Object>) {
+        for child in children {
+            self.add_child(child);
+        }
+    }
+
+    fn world_inverse(&self) -> Matrix {
+        &self.inverse * &self.parent_inverse
+    }
+
+    fn set_parent_inverse(&mut self, parent_inverse: Matrix) {
+        self.parent_inverse = parent_inverse;
+        self.update_children();
+    }
+
+    fn update_children(&mut self) {
+        let world_inverse = self.world_inverse();
+        if let Some(children) = self.group_children_mut() {
+            for child in children {
+                child.set_parent_inverse(world_inverse.clone());
+            }
+        }
+    }
+
+    // Converts a world-space point into this object's space, through every
+    // group the object sits in.
+    pub fn world_to_object(&self, point: &Point) -> Point {
+        &self.inverse * &(&self.parent_inverse * point)
+    }
+
+    // True if other is this object or sits anywhere beneath it. Children are
+    // stored by value, so the check goes by id.
+    pub fn includes(&self, other: &Object) -> bool {
+        self.id == other.id || self.get_children().iter().any(|child| child.includes(other))
+    }
+
+    // This object or the descendant with the given id.
+    pub fn find(&self, id: usize) -> Option<&Object> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.get_children().iter().find_map(|child| child.find(id))
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let local_ray = ray.transform(&self.inverse);
+        self.shape.local_intersect(self, &local_ray)
     }
 
+    // Like intersect, but adds the hits to xs, so a whole scene can share one
+    // list.
+    pub fn intersect_into<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        let local_ray = ray.transform(&self.inverse);
+        self.shape.local_intersect_into(self, &local_ray, xs);
+    }
+
+    // Parity test: a ray from the point crosses a closed surface an odd
+    // number of times from inside. Open shapes such as planes give no useful
+    // answer. The direction is skewed so it won't run along a face or edge.
+    pub fn contains_point(&self, world_point: &Point) -> bool {
+        if let Shape::Group(group) = &self.shape {
+            return group.get_children().iter().any(|child| child.contains_point(world_point));
+        }
+        let direction = Tuple::vector(0.267, 0.534, 0.802).normalize();
+        let ray = Ray::new(*world_point, direction).transform(&self.parent_inverse);
+        self.intersect(&ray).iter().filter(|i| i.t > 0.0).count() % 2 == 1
+    }
+
+    // Like intersect, but a tangent hit (two roots within EPSILON) becomes a
+    // single intersection, which is what CSG and container tracking expect.
+    pub fn intersect_dedup(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut xs = self.intersect(ray);
+        xs.dedup_by(|b, a| a.object.id == b.object.id && (a.t - b.t).abs() < EPSILON);
+        xs
+    }
+
+    // The transpose of the full world-to-object inverse carries the normal
+    // back out through every group, not just this object's own transform.
     pub fn normal_at(&self, world_point: &Point) -> Vector {
-        let object_point = self.transform.inverse() * *world_point;
-        let object_normal = self.shape.local_normal_at(&object_point);
-        let mut world_normal = self.transform.inverse().transpose() * object_normal;
+        let object_point = self.world_to_object(world_point);
+        let object_normal = self.shape.shading_normal_at(&object_point);
+        let mut world_normal = self.world_inverse().transpose() * object_normal;
         world_normal.3 = 0.0;
         world_normal.normalize()
     }
 
+    // The shape's own normal at a point already in object space, with no
+    // transform applied, for telling shape bugs from transform bugs.
+    pub fn local_normal_at(&self, object_point: &Point) -> Vector {
+        self.shape.local_normal_at(object_point)
+    }
+
+    // Bounds in the parent's space (world space for top-level objects).
+    pub fn bounds(&self) -> Bounds {
+        self.shape.bounds().transform(&self.transform)
+    }
+
+    // Texture coordinates at a world point, through the pattern's mapping or,
+    // if it has none, the shape's own.
+    pub fn uv_at(&self, world_point: &Point) -> (f64, f64) {
+        let mapping = match &self.get_material().pattern {
+            Some(pattern) if pattern.mapping != PatternMapping::Solid3D => pattern.mapping,
+            _ => self.shape.default_mapping(),
+        };
+        mapping.uv(self.world_to_object(world_point))
+    }
+
+    // Bounds in world space, through every enclosing group.
+    pub fn world_bounds(&self) -> Bounds {
+        let world_inverse = self.world_inverse();
+        if !world_inverse.is_invertible() {
+            return Bounds::infinite();
+        }
+        self.shape.bounds().transform(&world_inverse.inverse())
+    }
+
     pub fn get_transform(&self) -> &Matrix {
         &self.transform
     }
 
+    pub fn get_inverse_transform(&self) -> &Matrix {
+        &self.inverse
+    }
+
     pub fn set_transform(&mut self, transform: Matrix) {
         self.transform = transform;
+        self.invalidate_cache();
+    }
+
+    // Recomputes the cached inverse and pushes the new parent space down to
+    // every descendant. A singular transform leaves a NaN inverse, so rays miss the object
+    // instead of panicking mid-render, and World::validate reports it.
+    fn invalidate_cache(&mut self) {
+        self.inverse = if self.transform.is_invertible() {
+            self.transform.inverse()
+        } else {
+            Matrix::new(vec![f64::NAN; 16])
+        };
+        self.update_children();
     }
 
     pub fn with_transform(&self, transform: Matrix) -> Object {
@@ -64,12 +263,19 @@ impl Object {
         new_sphere
     }
 
-    pub fn get_material(&self) -> &Material {
-        &self.material
+    // Nothing writes materials mid-render, so the read lock never waits.
+    pub fn get_material(&self) -> RwLockReadGuard<'_, Material> {
+        self.material.read().unwrap()
     }
 
     pub fn set_material(&mut self, material: Material) {
-        self.material = material;
+        self.material = material.shared();
+    }
+
+    // Edits the material in place, so every object sharing it (clones
+    // included) sees the change.
+    pub fn update_material(&self, update: impl FnOnce(&mut Material)) {
+        update(&mut self.material.write().unwrap());
     }
 
     pub fn with_material(&self, material: Material) -> Object {
@@ -78,16 +284,101 @@ impl Object {
         new_object
     }
 
-    
+    pub fn set_shared_material(&mut self, material: SharedMaterial) {
+        self.material = material;
+    }
+
+    pub fn with_shared_material(&self, material: SharedMaterial) -> Object {
+        let mut new_object = self.clone();
+        new_object.set_shared_material(material);
+        new_object
+    }
+
+    pub fn set_casts_shadow(&mut self, casts_shadow: bool) {
+        self.casts_shadow = casts_shadow;
+    }
+
+    pub fn with_casts_shadow(&self, casts_shadow: bool) -> Object {
+        let mut new_object = self.clone();
+        new_object.set_casts_shadow(casts_shadow);
+        new_object
+    }
+
+    // A copy holding only what is on the layer. Leaf shapes are judged by
+    // their own layer; a group keeps just its matching children and is
+    // dropped when none are left, whatever layer the group itself is on.
+    pub fn on_layer(&self, layer: u32) -> Option<Object> {
+        if !matches!(self.shape, Shape::Group(_)) {
+            return (self.layer == layer).then(|| self.clone());
+        }
+        let kept: Vec<Object> = self.get_children().iter().filter_map(|child| child.on_layer(layer)).collect();
+        if kept.is_empty() {
+            return None;
+        }
+        let mut group = self.clone();
+        *group.children_mut().unwrap() = kept;
+        Some(group)
+    }
+
+    pub fn set_layer(&mut self, layer: u32) {
+        self.layer = layer;
+    }
+
+    pub fn with_layer(&self, layer: u32) -> Object {
+        let mut new_object = self.clone();
+        new_object.set_layer(layer);
+        new_object
+    }
+}
+
+pub struct ChildrenMut<'a> {
+    group: &'a mut Object,
+}
+
+impl Deref for ChildrenMut<'_> {
+    type Target = Vec<Object>;
+
+    fn deref(&self) -> &Vec<Object> {
+        self.group.get_children()
+    }
+}
+
+impl DerefMut for ChildrenMut<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<Object> {
+        self.group.group_children_mut().unwrap()
+    }
+}
+
+impl Drop for ChildrenMut<'_> {
+    fn drop(&mut self) {
+        self.group.update_children();
+    }
+}
+
+// Ids identify an object rather than describe it, so they take no part in equality.
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape
+            && self.transform == other.transform
+            && (Arc::ptr_eq(&self.material, &other.material) || *self.get_material() == *other.get_material())
+            && self.parent == other.parent
+            && self.casts_shadow == other.casts_shadow
+            && self.layer == other.layer
+    }
 }
 
 impl Default for Object {
     fn default() -> Self {
         Object {
+            id: next_id(),
             shape: Shape::Sphere(Sphere::new()),
             transform: Matrix::identity(),
-            material: Material::new(),
+            material: Material::new().shared(),
             parent: None,
+            casts_shadow: true,
+            layer: 0,
+            inverse: Matrix::identity(),
+            parent_inverse: Matrix::identity(),
         }
     }
 }
@@ -103,10 +394,46 @@ mod tests {
     use crate::matrix::Matrix;
     use crate::transformation::Transformation;
 
+    fn saved_ray(object: &Object) -> Option<Ray> {
+        match object.get_shape() {
+            Shape::TestShape(s) => s.saved_ray(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn a_test_shape_has_no_saved_ray_before_intersecting() {
+        let s = Object::test_shape();
+        assert_eq!(saved_ray(&s), None);
+    }
+
+    #[test]
+    fn every_object_gets_its_own_id() {
+        let a = Object::sphere();
+        let b = Object::sphere();
+        assert_ne!(a.id, b.id);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn objects_can_share_one_material() {
+        let glass = Material::glass().shared();
+        let a = Object::sphere().with_shared_material(glass.clone());
+        let b = Object::cube().with_shared_material(glass.clone());
+        assert!(Arc::ptr_eq(&a.material, &b.material));
+        assert_eq!(Arc::strong_count(&glass), 3);
+
+        let c = a.with_material(Material::water());
+        assert!(!Arc::ptr_eq(&a.material, &c.material));
+        assert_eq!(*c.get_material(), Material::water());
+        assert_eq!(*a.get_material(), Material::glass());
+    }
+
     #[test]
     fn default_transform() {
         let s = Object::test_shape();
         assert_eq!(*s.get_transform(), Matrix::identity());
+        assert_eq!(*s.get_inverse_transform(), Matrix::identity());
     }
 
     #[test]
@@ -115,6 +442,7 @@ mod tests {
         let t = Matrix::translation(2.0, 3.0, 4.0);
         s.set_transform(t.clone());
         assert_eq!(*s.get_transform(), t);
+        assert_eq!(*s.get_inverse_transform(), t.inverse());
     }
 
     #[test]
@@ -138,6 +466,9 @@ mod tests {
         let xs = s.intersect(&r);
         assert_eq!(s.get_transform(), &Matrix::scaling(2.0, 2.0, 2.0));
         assert_eq!(xs.len(), 0);
+        let saved_ray = saved_ray(&s).unwrap();
+        assert_eq!(saved_ray.origin, Tuple::point(0.0, 0.0, -2.5));
+        assert_eq!(saved_ray.direction, Tuple::vector(0.0, 0.0, 0.5));
     }
 
     #[test]
@@ -147,6 +478,9 @@ mod tests {
         let xs = s.intersect(&r);
         assert_eq!(s.get_transform(), &Matrix::translation(5.0, 0.0, 0.0));
         assert_eq!(xs.len(), 0);
+        let saved_ray = saved_ray(&s).unwrap();
+        assert_eq!(saved_ray.origin, Tuple::point(-5.0, 0.0, -5.0));
+        assert_eq!(saved_ray.direction, Tuple::vector(0.0, 0.0, 1.0));
     }
 
     #[test]
@@ -168,4 +502,198 @@ mod tests {
         assert!((n.1 - 0.97014).abs() < delta);
         assert!((n.2 + 0.24254).abs() < delta);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn a_group_includes_its_grandchildren() {
+        let s = Object::sphere();
+        let mut inner = Object::group();
+        inner.add_child(s.clone());
+        let mut outer = Object::group();
+        outer.add_child(inner.clone());
+        assert!(outer.includes(&s));
+        assert!(outer.includes(&inner));
+        assert!(outer.includes(&outer));
+        assert!(!inner.includes(&outer));
+    }
+
+    #[test]
+    fn a_group_does_not_include_an_unrelated_object() {
+        let mut g = Object::group();
+        g.add_child(Object::sphere());
+        assert!(!g.includes(&Object::sphere()));
+        assert!(!Object::sphere().includes(&g));
+    }
+
+    #[test]
+    fn objects_get_distinct_ids_and_clones_keep_them() {
+        let a = Object::sphere();
+        let b = Object::sphere();
+        assert_ne!(a.id, b.id);
+        assert_eq!(a.clone().id, a.id);
+        assert_eq!(a.with_transform(Matrix::translation(1.0, 0.0, 0.0)).id, a.id);
+    }
+
+    #[test]
+    fn converting_a_point_from_world_to_object_space() {
+        let mut g1 = Object::group().with_transform(Matrix::rotation_y(std::f64::consts::PI / 2.0));
+        let mut g2 = Object::group().with_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        g2.add_child(Object::sphere().with_transform(Matrix::translation(5.0, 0.0, 0.0)));
+        g1.add_child(g2);
+        let s = &g1.get_children()[0].get_children()[0];
+        let p = s.world_to_object(&Tuple::point(-2.0, 0.0, -10.0));
+        let epsilon = 1e-10;
+        assert!((p.0 - 0.0).abs() < epsilon);
+        assert!((p.1 - 0.0).abs() < epsilon);
+        assert!((p.2 + 1.0).abs() < epsilon);
+    }
+
+    #[test]
+    fn moving_a_group_after_adding_children_moves_them_too() {
+        let mut g = Object::group();
+        g.add_child(Object::sphere());
+        g.set_transform(Matrix::translation(3.0, 0.0, 0.0));
+        let s = &g.get_children()[0];
+        assert_eq!(s.world_to_object(&Tuple::point(3.0, 1.0, 0.0)), Tuple::point(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn world_bounds_follow_a_group_that_moves() {
+        let mut g = Object::group();
+        g.add_child(Object::sphere());
+        let before = g.get_children()[0].world_bounds();
+        assert_eq!(before.min, Tuple::point(-1.0, -1.0, -1.0));
+
+        g.set_transform(Matrix::translation(10.0, 0.0, 0.0));
+        let after = g.get_children()[0].world_bounds();
+        assert_eq!(after.min, Tuple::point(9.0, -1.0, -1.0));
+        assert_eq!(after.max, Tuple::point(11.0, 1.0, 1.0));
+        assert_eq!(g.world_bounds(), after);
+    }
+
+    #[test]
+    fn children_pushed_through_children_mut_take_the_group_space() {
+        let mut g = Object::group().with_transform(Matrix::translation(0.0, 5.0, 0.0));
+        g.children_mut().unwrap().push(Object::sphere());
+        let child = &g.get_children()[0];
+        assert_eq!(child.world_to_object(&Tuple::point(0.0, 6.0, 0.0)), Tuple::point(0.0, 1.0, 0.0));
+        assert_eq!(child.normal_at(&Tuple::point(0.0, 6.0, 0.0)), Tuple::vector(0.0, 1.0, 0.0));
+
+        let mut inner = Object::group();
+        inner.add_child(Object::sphere());
+        g.children_mut().unwrap()[0] = inner;
+        let nested = &g.get_children()[0].get_children()[0];
+        assert_eq!(nested.world_to_object(&Tuple::point(0.0, 4.0, 0.0)), Tuple::point(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn setting_a_child_transform_through_children_mut() {
+        let mut g = Object::group().with_transform(Matrix::translation(0.0, 5.0, 0.0));
+        g.add_child(Object::sphere());
+        g.children_mut().unwrap()[0].set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        let b = g.get_children()[0].world_bounds();
+        assert_eq!(b.min, Tuple::point(-2.0, 3.0, -2.0));
+        assert_eq!(b.max, Tuple::point(2.0, 7.0, 2.0));
+    }
+
+    #[test]
+    fn the_normal_on_a_non_uniformly_scaled_child_of_a_rotated_group() {
+        let mut g = Object::group().with_transform(Matrix::rotation_y(std::f64::consts::PI / 3.0).translate(1.0, -2.0, 0.5));
+        g.add_child(Object::sphere().with_transform(Matrix::scaling(1.0, 2.0, 3.0).rotate_z(0.4)));
+        let s = &g.get_children()[0];
+
+        // Reference: compose the whole stack by hand and push the normal
+        // through the inverse transpose of that one matrix.
+        let m = Matrix::translation(1.0, -2.0, 0.5)
+            * Matrix::rotation_y(std::f64::consts::PI / 3.0)
+            * Matrix::rotation_z(0.4)
+            * Matrix::scaling(1.0, 2.0, 3.0);
+        let k = 1.0 / 3.0_f64.sqrt();
+        let world_point = &m * &Tuple::point(k, k, k);
+        let mut expected = m.inverse().transpose() * Tuple::vector(k, k, k);
+        expected.3 = 0.0;
+        let expected = expected.normalize();
+
+        let n = s.normal_at(&world_point);
+        assert!((n.0 - expected.0).abs() < 1e-9);
+        assert!((n.1 - expected.1).abs() < 1e-9);
+        assert!((n.2 - expected.2).abs() < 1e-9);
+        assert_eq!(n.3, 0.0);
+    }
+
+    #[test]
+    fn every_shape_intersects_as_its_own_object() {
+        let r = Ray::new(Tuple::point(0.0, 0.5, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let plane = Object::plane().with_transform(Matrix::rotation_x(std::f64::consts::PI / 2.0));
+        for object in [Object::sphere(), plane, Object::cube(), Object::cylinder(), Object::cone()] {
+            let xs = object.intersect(&r);
+            assert!(!xs.is_empty());
+            assert!(xs.iter().all(|i| std::ptr::eq(i.object, &object)));
+        }
+
+        let mut g = Object::group();
+        g.add_child(Object::sphere());
+        g.add_child(Object::cube().with_transform(Matrix::translation(0.0, 0.0, 5.0)));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 4);
+        assert!(xs[..2].iter().all(|i| std::ptr::eq(i.object, &g.get_children()[0])));
+        assert!(xs[2..].iter().all(|i| std::ptr::eq(i.object, &g.get_children()[1])));
+    }
+
+    #[test]
+    fn local_normals_skip_the_object_transform() {
+        let s = Object::sphere().with_transform(Matrix::scaling(1.0, 0.5, 1.0));
+        let k = 2.0_f64.sqrt() / 2.0;
+        let object_point = Tuple::point(0.0, k, -k);
+        assert_eq!(s.local_normal_at(&object_point), Tuple::vector(0.0, k, -k));
+
+        let n = s.normal_at(&Tuple::point(0.0, k / 2.0, -k));
+        assert!((n - Tuple::vector(0.0, 2.0, -1.0).normalize()).magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn counting_children_and_descendants() {
+        let mut inner = Object::group();
+        inner.add_children(vec![Object::sphere(), Object::sphere(), Object::sphere()]);
+        let mut outer = Object::group();
+        outer.add_child(inner);
+        assert_eq!(outer.child_count(), 1);
+        assert_eq!(outer.descendant_count(), 4);
+        assert_eq!(outer.get_children()[0].child_count(), 3);
+
+        let s = Object::sphere();
+        assert_eq!(s.child_count(), 0);
+        assert_eq!(s.descendant_count(), 0);
+    }
+
+    #[test]
+    fn points_inside_and_outside_closed_shapes() {
+        let s = Object::sphere();
+        assert!(s.contains_point(&Tuple::point(0.0, 0.0, 0.0)));
+        assert!(!s.contains_point(&Tuple::point(2.0, 0.0, 0.0)));
+
+        let big = Object::sphere().with_transform(Matrix::scaling(3.0, 3.0, 3.0));
+        assert!(big.contains_point(&Tuple::point(2.0, 0.0, 0.0)));
+
+        let mut cylinder = Cylinder::new();
+        cylinder.minimum = 0.0;
+        cylinder.maximum = 2.0;
+        cylinder.closed = true;
+        let c = Object::new(Shape::Cylinder(cylinder));
+        assert!(c.contains_point(&Tuple::point(0.5, 1.0, 0.0)));
+        assert!(!c.contains_point(&Tuple::point(0.5, 3.0, 0.0)));
+
+        assert!(Object::cube().contains_point(&Tuple::point(0.9, -0.9, 0.9)));
+        assert!(!Object::cube().contains_point(&Tuple::point(1.1, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn a_group_contains_the_points_inside_its_children() {
+        let mut g = Object::group().with_transform(Matrix::translation(10.0, 0.0, 0.0));
+        g.add_child(Object::sphere());
+        g.add_child(Object::cube().with_transform(Matrix::translation(0.5, 0.0, 0.0)));
+        assert!(g.contains_point(&Tuple::point(10.0, 0.0, 0.0)));
+        assert!(g.contains_point(&Tuple::point(11.3, 0.0, 0.0)));
+        assert!(!g.contains_point(&Tuple::point(0.0, 0.0, 0.0)));
+        assert!(!g.contains_point(&Tuple::point(12.0, 0.0, 0.0)));
+    }
+}
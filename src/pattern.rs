@@ -1,4 +1,4 @@
-use crate::{matrix::Matrix, object::Object, patterns::{checkers::Checkers, gradient::Gradient, ring::Ring, stripe::Stripe, test_pattern::TestPattern}, tuple::{Color, Point}};
+use crate::{axis::Axis, matrix::Matrix, transformation::Transformation, object::Object, patterns::{mapping::PatternMapping, checkers::Checkers, gradient::Gradient, ring::Ring, solid_color::SolidColor, stripe::Stripe, test_pattern::TestPattern}, tuple::{Color, Point, Tuple}};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum PatternEnum {
@@ -7,12 +7,14 @@ pub enum PatternEnum {
     Gradient(Gradient),
     Ring(Ring),
     Checkers(Checkers),
+    SolidColor(SolidColor),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Pattern {
     pub pattern: PatternEnum,
     pub transform: Matrix,
+    pub mapping: PatternMapping,
 }
 
 impl Pattern {
@@ -21,6 +23,7 @@ impl Pattern {
         Pattern {
             pattern: PatternEnum::TestPattern(TestPattern::new()),
             transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
         }
     }
 
@@ -29,6 +32,26 @@ impl Pattern {
         Pattern {
             pattern: PatternEnum::Stripe(Stripe::new(a, b)),
             transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
+        }
+    }
+
+    // Stripes alternate along the given axis; the rotation is baked into the
+    // pattern transform, so further transforms chain on top of it.
+    pub fn stripe_along(axis: Axis, a: Color, b: Color) -> Pattern {
+        let transform = match axis {
+            Axis::X => Matrix::identity(),
+            Axis::Y => Matrix::rotation_z(std::f64::consts::PI / 2.0),
+            Axis::Z => Matrix::rotation_y(-std::f64::consts::PI / 2.0),
+        };
+        Pattern::stripe(a, b).with_transform(transform)
+    }
+
+    pub fn stripe_palette(palette: Vec<Color>) -> Pattern {
+        Pattern {
+            pattern: PatternEnum::Stripe(Stripe::palette(palette)),
+            transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
         }
     }
 
@@ -36,6 +59,7 @@ impl Pattern {
         Pattern {
             pattern: PatternEnum::Gradient(Gradient::new(a, b)),
             transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
         }
     }
 
@@ -43,6 +67,7 @@ impl Pattern {
         Pattern {
             pattern: PatternEnum::Ring(Ring::new(a, b)),
             transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
         }
     }
 
@@ -50,6 +75,31 @@ impl Pattern {
         Pattern {
             pattern: PatternEnum::Checkers(Checkers::new(a, b)),
             transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
+        }
+    }
+
+    pub fn checkers_palette(palette: Vec<Color>) -> Pattern {
+        Pattern {
+            pattern: PatternEnum::Checkers(Checkers::palette(palette)),
+            transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
+        }
+    }
+
+    pub fn checkers_planar(a: Color, b: Color) -> Pattern {
+        Pattern {
+            pattern: PatternEnum::Checkers(Checkers::planar(a, b)),
+            transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
+        }
+    }
+
+    pub fn solid(color: Color) -> Pattern {
+        Pattern {
+            pattern: PatternEnum::SolidColor(SolidColor::new(color)),
+            transform: Matrix::identity(),
+            mapping: PatternMapping::Solid3D,
         }
     }
 
@@ -60,12 +110,20 @@ impl Pattern {
             PatternEnum::Gradient(p) => p.gradient_at(point),
             PatternEnum::Ring(p) => p.ring_at(point),
             PatternEnum::Checkers(p) => p.checkers_at(point),
+            PatternEnum::SolidColor(p) => p.solid_color_at(point),
         }
     }
 
     pub fn pattern_at_shape(&self, shape: &Object, world_point: Point) -> Color {
-        let object_point = shape.get_transform().inverse() * world_point;
-        let pattern_point = self.get_transform().inverse() * object_point;
+        let object_point = shape.world_to_object(&world_point);
+        let mapped_point = match self.mapping {
+            PatternMapping::Solid3D => object_point,
+            mapping => {
+                let (u, v) = mapping.uv(object_point);
+                Tuple::point(u, v, 0.0)
+            }
+        };
+        let pattern_point = self.get_transform().inverse() * mapped_point;
         self.pattern_at(pattern_point)
     }
 
@@ -82,12 +140,52 @@ impl Pattern {
         new_pattern.set_transform(transform);
         new_pattern
     }
+
+    pub fn translate(&self, x: f64, y: f64, z: f64) -> Pattern {
+        self.with_transform(self.transform.translate(x, y, z))
+    }
+
+    pub fn scale(&self, x: f64, y: f64, z: f64) -> Pattern {
+        self.with_transform(self.transform.scale(x, y, z))
+    }
+
+    pub fn rotate_x(&self, r: f64) -> Pattern {
+        self.with_transform(self.transform.rotate_x(r))
+    }
+
+    pub fn rotate_y(&self, r: f64) -> Pattern {
+        self.with_transform(self.transform.rotate_y(r))
+    }
+
+    pub fn rotate_z(&self, r: f64) -> Pattern {
+        self.with_transform(self.transform.rotate_z(r))
+    }
+
+    // Blends the colors within softness of each boundary instead of switching
+    // abruptly. Only stripes and checkers have hard edges to soften.
+    pub fn with_softness(&self, softness: f64) -> Pattern {
+        let mut new_pattern = self.clone();
+        new_pattern.pattern = match new_pattern.pattern {
+            PatternEnum::Stripe(p) => PatternEnum::Stripe(p.with_softness(softness)),
+            PatternEnum::Checkers(p) => PatternEnum::Checkers(p.with_softness(softness)),
+            other => other,
+        };
+        new_pattern
+    }
+
+    // Non-solid mappings feed (u, v, 0) through the pattern transform in place
+    // of the object point.
+    pub fn with_mapping(&self, mapping: PatternMapping) -> Pattern {
+        let mut new_pattern = self.clone();
+        new_pattern.mapping = mapping;
+        new_pattern
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{matrix::Matrix, object::Object, transformation::Transformation, tuple::Tuple};
+    use crate::{color::Colors, matrix::Matrix, object::Object, tuple::Tuple};
 
     #[test]
     fn the_default_pattern_transformation() {
@@ -120,4 +218,117 @@ mod tests {
         let c = pattern.pattern_at_shape(&obj, Tuple::point(2.5, 3.0, 3.5));
         assert_eq!(c, Tuple::color(0.75, 0.5, 0.25));
     }
+
+    #[test]
+    fn chaining_pattern_transformations() {
+        let pattern = Pattern::stripe(Color::white(), Color::black())
+            .scale(2.0, 2.0, 2.0)
+            .translate(1.0, 0.0, 0.0);
+        assert_eq!(pattern.get_transform(), &(Matrix::translation(1.0, 0.0, 0.0) * Matrix::scaling(2.0, 2.0, 2.0)));
+
+        let rotated = Pattern::test_pattern().rotate_y(1.0).rotate_x(0.5).rotate_z(0.25);
+        assert_eq!(rotated.get_transform(), &(Matrix::rotation_z(0.25) * (Matrix::rotation_x(0.5) * Matrix::rotation_y(1.0))));
+    }
+
+    #[test]
+    fn the_default_pattern_mapping_is_solid() {
+        assert_eq!(Pattern::test_pattern().mapping, PatternMapping::Solid3D);
+    }
+
+    #[test]
+    fn a_spherically_mapped_stripe_follows_longitude() {
+        let sphere = Object::sphere();
+        let stripes = Pattern::stripe(Color::white(), Color::black())
+            .with_transform(Matrix::scaling(0.125, 1.0, 1.0));
+        let mapped = stripes.with_mapping(PatternMapping::Spherical);
+
+        // Same longitude, different latitudes: one stripe.
+        let a = Tuple::point(0.0, 0.0, -1.0);
+        let b = Tuple::point(0.0, 0.8, -0.6);
+        let c = Tuple::point(0.0, -0.8, -0.6);
+        assert_eq!(mapped.pattern_at_shape(&sphere, a), mapped.pattern_at_shape(&sphere, b));
+        assert_eq!(mapped.pattern_at_shape(&sphere, a), mapped.pattern_at_shape(&sphere, c));
+
+        // A sixteenth of a turn away lands in the neighbouring stripe.
+        let step = std::f64::consts::PI / 8.0;
+        let d = Tuple::point(-(step.sin()), 0.0, -(step.cos()));
+        assert_ne!(mapped.pattern_at_shape(&sphere, a), mapped.pattern_at_shape(&sphere, d));
+
+        // The solid pattern stripes along x regardless of latitude or longitude.
+        assert_eq!(stripes.pattern_at_shape(&sphere, a), Color::white());
+        assert_eq!(stripes.pattern_at_shape(&sphere, Tuple::point(0.2, 0.0, -0.98)), Color::black());
+    }
+
+    #[test]
+    fn a_stripe_along_z_alternates_in_z_and_is_constant_in_x() {
+        let obj = Object::sphere();
+        let pattern = Pattern::stripe_along(Axis::Z, Colors::white(), Colors::black());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(0.0, 0.0, 0.5)), Colors::white());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(0.0, 0.0, 1.5)), Colors::black());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(0.0, 0.0, 2.5)), Colors::white());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(1.5, 0.0, 0.5)), Colors::white());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(-3.5, 0.0, 1.5)), Colors::black());
+    }
+
+    #[test]
+    fn a_stripe_along_y_alternates_in_y() {
+        let obj = Object::sphere();
+        let pattern = Pattern::stripe_along(Axis::Y, Colors::white(), Colors::black());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(0.0, 0.5, 0.0)), Colors::white());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(0.0, 1.5, 0.0)), Colors::black());
+        assert_eq!(pattern.pattern_at_shape(&obj, Tuple::point(1.5, 0.5, 2.5)), Colors::white());
+    }
+
+    #[test]
+    fn a_pattern_on_a_child_follows_the_group_transform() {
+        let mut g = Object::group().with_transform(Matrix::translation(1.0, 0.0, 0.0));
+        g.add_child(Object::sphere().with_transform(Matrix::scaling(2.0, 2.0, 2.0)));
+        let s = &g.get_children()[0];
+        let pattern = Pattern::stripe(Colors::white(), Colors::black());
+
+        // World x = 2.5 is group x = 1.5 and object x = 0.75, so white; the
+        // object transform alone would give 1.25 and black.
+        assert_eq!(pattern.pattern_at_shape(s, Tuple::point(2.5, 0.0, 0.0)), Colors::white());
+        assert_eq!(pattern.pattern_at_shape(s, Tuple::point(3.5, 0.0, 0.0)), Colors::black());
+    }
+
+    // Colour changes met going once around a cylinder of the given radius at
+    // height y.
+    fn bands_around(pattern: &Pattern, cylinder: &Object, radius: f64, y: f64) -> usize {
+        let samples = 720;
+        let colors: Vec<Color> = (0..samples)
+            .map(|i| {
+                let theta = (i as f64 + 0.5) / samples as f64 * 2.0 * std::f64::consts::PI;
+                pattern.pattern_at_shape(cylinder, Tuple::point(radius * theta.cos(), y, radius * theta.sin()))
+            })
+            .collect();
+        (0..samples).filter(|&i| colors[i] != colors[(i + 1) % samples]).count()
+    }
+
+    #[test]
+    fn cylindrical_stripes_wrap_the_barrel_in_a_fixed_number_of_bands() {
+        let wrapped = Pattern::stripe(Color::white(), Color::black())
+            .scale(1.0 / 8.0, 1.0, 1.0)
+            .with_mapping(PatternMapping::Cylindrical);
+        let sliced = Pattern::stripe(Color::white(), Color::black()).scale(1.0 / 8.0, 1.0, 1.0);
+
+        for (radius, turn) in [(1.0, 0.0), (1.0, 0.7), (3.0, 0.0), (3.0, 2.1)] {
+            let cylinder = Object::cylinder().with_transform(Matrix::scaling(radius, 1.0, radius).rotate_y(turn));
+            assert_eq!(bands_around(&wrapped, &cylinder, radius, 0.25), 8);
+            assert_eq!(bands_around(&wrapped, &cylinder, radius, 0.75), 8);
+        }
+
+        // Slicing by object x packs the bands unevenly and their count
+        // follows the sampling, not the barrel.
+        let cylinder = Object::cylinder();
+        assert_ne!(bands_around(&sliced, &cylinder, 1.0, 0.25), 8);
+
+        // The bands run straight up the barrel.
+        let cylinder = Object::cylinder();
+        for theta in [0.3_f64, 1.4, 2.9, 4.4] {
+            let low = wrapped.pattern_at_shape(&cylinder, Tuple::point(theta.cos(), 0.1, theta.sin()));
+            let high = wrapped.pattern_at_shape(&cylinder, Tuple::point(theta.cos(), 0.9, theta.sin()));
+            assert_eq!(low, high);
+        }
+    }
 }
@@ -1,22 +1,53 @@
-use crate::tuple::{Color, Point};
+use crate::{patterns::stripe::soft_cells, tuple::{Color, Point, Tuple}};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Checkers {
-    pub a: Color,
-    pub b: Color,
+    pub palette: Vec<Color>,
+    pub planar: bool,
+    pub softness: f64,
 }
 
 impl Checkers {
     pub fn new(a: Color, b: Color) -> Checkers {
-        Checkers { a, b }
+        Checkers::palette(vec![a, b])
     }
 
+    pub fn planar(a: Color, b: Color) -> Checkers {
+        Checkers { planar: true, ..Checkers::new(a, b) }
+    }
+
+    // Neighbouring cells step through the colors in order, so four colors
+    // give a harlequin floor.
+    pub fn palette(palette: Vec<Color>) -> Checkers {
+        assert!(!palette.is_empty(), "a checkers palette needs at least one color");
+        Checkers { palette, planar: false, softness: 0.0 }
+    }
+
+    pub fn with_softness(mut self, softness: f64) -> Checkers {
+        self.softness = softness;
+        self
+    }
+
+    // Softness blends each axis separately, so a corner mixes all the cells
+    // that meet there.
     pub fn checkers_at(&self, point: Point) -> Color {
-        if (point.0.floor() as i32 + point.1.floor() as i32 + point.2.floor() as i32) % 2 == 0 {
-            self.a
-        } else {
-            self.b
+        let len = self.palette.len() as i64;
+        let xs = soft_cells(point.0, self.softness);
+        let ys = if self.planar { [(0, 1.0), (0, 0.0)] } else { soft_cells(point.1, self.softness) };
+        let zs = soft_cells(point.2, self.softness);
+
+        let mut color = Tuple::color(0.0, 0.0, 0.0);
+        for (x, wx) in xs {
+            for (y, wy) in ys {
+                for (z, wz) in zs {
+                    let weight = wx * wy * wz;
+                    if weight > 0.0 {
+                        color = color + self.palette[(x + y + z).rem_euclid(len) as usize] * weight;
+                    }
+                }
+            }
         }
+        color
     }
 }
 
@@ -48,4 +79,44 @@ mod tests {
         assert_eq!(pattern.pattern_at(Tuple::point(0.0, 0.0, 0.99)), Color::white());
         assert_eq!(pattern.pattern_at(Tuple::point(0.0, 0.0, 1.01)), Color::black());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn planar_checkers_ignore_y() {
+        let pattern = Pattern::checkers_planar(Color::white(), Color::black());
+        let solid = Pattern::checkers(Color::white(), Color::black());
+        for y in [0.0, 0.5, 1.01, 2.5, -0.3] {
+            assert_eq!(pattern.pattern_at(Tuple::point(0.0, y, 0.0)), Color::white());
+        }
+        assert_eq!(solid.pattern_at(Tuple::point(0.0, 1.01, 0.0)), Color::black());
+    }
+
+    #[test]
+    fn planar_checkers_repeat_in_x_and_z() {
+        let pattern = Pattern::checkers_planar(Color::white(), Color::black());
+        assert_eq!(pattern.pattern_at(Tuple::point(0.99, 3.0, 0.0)), Color::white());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.01, 3.0, 0.0)), Color::black());
+        assert_eq!(pattern.pattern_at(Tuple::point(0.0, 3.0, 1.01)), Color::black());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.01, 3.0, 1.01)), Color::white());
+        assert_eq!(pattern.pattern_at(Tuple::point(-0.5, 3.0, 0.5)), Color::black());
+    }
+
+    #[test]
+    fn four_color_checkers_step_through_the_palette() {
+        let palette = vec![Color::red(), Color::green(), Color::blue(), Color::yellow()];
+        let pattern = Pattern::checkers_palette(palette);
+        assert_eq!(pattern.pattern_at(Tuple::point(0.5, 0.5, 0.5)), Color::red());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.5, 0.5, 0.5)), Color::green());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.5, 1.5, 0.5)), Color::blue());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.5, 1.5, 1.5)), Color::yellow());
+        assert_eq!(pattern.pattern_at(Tuple::point(-0.5, 0.5, 0.5)), Color::yellow());
+    }
+
+    #[test]
+    fn soft_checkers_blend_across_a_cell_boundary() {
+        let pattern = Pattern::checkers(Color::white(), Color::black()).with_softness(0.2);
+        assert_eq!(pattern.pattern_at(Tuple::point(0.5, 0.5, 0.5)), Color::white());
+        let c = pattern.pattern_at(Tuple::point(1.0, 0.5, 0.5));
+        assert!((c.0 - 0.5).abs() < 1e-10);
+        assert!((c.2 - 0.5).abs() < 1e-10);
+    }
+}
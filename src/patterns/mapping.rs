@@ -0,0 +1,108 @@
+use std::f64::consts::PI;
+
+use crate::tuple::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PatternMapping {
+    Solid3D,
+    Spherical,
+    Planar,
+    Cylindrical,
+    Cubic,
+}
+
+impl PatternMapping {
+    // Maps an object-space point to (u, v), each in [0, 1]. Solid3D has no uv
+    // and is handled by the caller.
+    pub fn uv(&self, point: Point) -> (f64, f64) {
+        match self {
+            PatternMapping::Solid3D => (point.0, point.1),
+            PatternMapping::Spherical => spherical_uv(point),
+            PatternMapping::Planar => (point.0.rem_euclid(1.0), point.2.rem_euclid(1.0)),
+            PatternMapping::Cylindrical => {
+                let theta = point.0.atan2(point.2);
+                let raw_u = theta / (2.0 * PI);
+                (1.0 - (raw_u + 0.5), point.1.rem_euclid(1.0))
+            }
+            PatternMapping::Cubic => cubic_uv(point),
+        }
+    }
+}
+
+fn spherical_uv(point: Point) -> (f64, f64) {
+    let theta = point.0.atan2(point.2);
+    let radius = (point.0 * point.0 + point.1 * point.1 + point.2 * point.2).sqrt();
+    let phi = (point.1 / radius).acos();
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), 1.0 - phi / PI)
+}
+
+fn cubic_uv(point: Point) -> (f64, f64) {
+    let (x, y, z) = (point.0, point.1, point.2);
+    let coord = |c: f64| c.rem_euclid(2.0) / 2.0;
+    let abs_max = x.abs().max(y.abs()).max(z.abs());
+
+    if abs_max == x {
+        (coord(1.0 - z), coord(y + 1.0))
+    } else if abs_max == -x {
+        (coord(z + 1.0), coord(y + 1.0))
+    } else if abs_max == y {
+        (coord(x + 1.0), coord(1.0 - z))
+    } else if abs_max == -y {
+        (coord(x + 1.0), coord(z + 1.0))
+    } else if abs_max == z {
+        (coord(x + 1.0), coord(y + 1.0))
+    } else {
+        (coord(1.0 - x), coord(y + 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tuple::Tuple;
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        assert!((actual.0 - expected.0).abs() < 1e-9);
+        assert!((actual.1 - expected.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn using_a_spherical_mapping_on_a_3d_point() {
+        let m = PatternMapping::Spherical;
+        assert_uv(m.uv(Tuple::point(0.0, 0.0, -1.0)), (0.0, 0.5));
+        assert_uv(m.uv(Tuple::point(1.0, 0.0, 0.0)), (0.25, 0.5));
+        assert_uv(m.uv(Tuple::point(0.0, 0.0, 1.0)), (0.5, 0.5));
+        assert_uv(m.uv(Tuple::point(-1.0, 0.0, 0.0)), (0.75, 0.5));
+        assert_uv(m.uv(Tuple::point(0.0, 1.0, 0.0)), (0.5, 1.0));
+        assert_uv(m.uv(Tuple::point(0.0, -1.0, 0.0)), (0.5, 0.0));
+    }
+
+    #[test]
+    fn using_a_planar_mapping_on_a_3d_point() {
+        let m = PatternMapping::Planar;
+        assert_uv(m.uv(Tuple::point(0.25, 0.0, 0.5)), (0.25, 0.5));
+        assert_uv(m.uv(Tuple::point(0.25, 0.0, -0.25)), (0.25, 0.75));
+        assert_uv(m.uv(Tuple::point(1.25, 0.5, -1.75)), (0.25, 0.25));
+    }
+
+    #[test]
+    fn using_a_cylindrical_mapping_on_a_3d_point() {
+        let m = PatternMapping::Cylindrical;
+        assert_uv(m.uv(Tuple::point(0.0, 0.0, -1.0)), (0.0, 0.0));
+        assert_uv(m.uv(Tuple::point(0.0, 0.5, -1.0)), (0.0, 0.5));
+        assert_uv(m.uv(Tuple::point(1.0, 0.5, 0.0)), (0.25, 0.5));
+        assert_uv(m.uv(Tuple::point(0.0, 0.75, 1.0)), (0.5, 0.75));
+    }
+
+    #[test]
+    fn using_a_cubic_mapping_on_each_face() {
+        let m = PatternMapping::Cubic;
+        assert_uv(m.uv(Tuple::point(-0.5, 0.5, 1.0)), (0.25, 0.75));
+        assert_uv(m.uv(Tuple::point(0.5, -0.5, -1.0)), (0.25, 0.25));
+        assert_uv(m.uv(Tuple::point(-1.0, 0.5, -0.5)), (0.25, 0.75));
+        assert_uv(m.uv(Tuple::point(1.0, -0.5, 0.5)), (0.25, 0.25));
+        assert_uv(m.uv(Tuple::point(-0.5, 1.0, -0.5)), (0.25, 0.75));
+        assert_uv(m.uv(Tuple::point(0.5, -1.0, 0.5)), (0.75, 0.75));
+    }
+}
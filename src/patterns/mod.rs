@@ -2,4 +2,6 @@ pub mod test_pattern;
 pub mod stripe;
 pub mod gradient;
 pub mod ring;
-pub mod checkers;
\ No newline at end of file
+pub mod checkers;
+pub mod solid_color;
+pub mod mapping;
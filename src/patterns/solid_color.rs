@@ -0,0 +1,43 @@
+use crate::tuple::{Color, Point};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolidColor {
+    pub color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor { color }
+    }
+
+    pub fn solid_color_at(&self, _point: Point) -> Color {
+        self.color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Colors, light::Light, material::Material, object::Object, pattern::Pattern, tuple::Tuple};
+
+    #[test]
+    fn a_solid_pattern_is_the_same_color_everywhere() {
+        let pattern = Pattern::solid(Color::red());
+        assert_eq!(pattern.pattern_at(Tuple::point(0.0, 0.0, 0.0)), Color::red());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.5, -2.0, 3.25)), Color::red());
+        assert_eq!(pattern.pattern_at(Tuple::point(-100.0, 42.0, 0.5)), Color::red());
+    }
+
+    #[test]
+    fn lighting_with_a_solid_pattern_matches_a_plain_color() {
+        let plain = Material::new().with_color(Color::red());
+        let solid = Material::new().with_pattern(Pattern::solid(Color::red()));
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = Light::new(Tuple::point(0.0, 10.0, -10.0), Color::white());
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let c1 = plain.lighting(&Object::test_shape(), &light, position, eyev, normalv, false);
+        let c2 = solid.lighting(&Object::test_shape(), &light, position, eyev, normalv, false);
+        assert_eq!(c1, c2);
+    }
+}
@@ -1,22 +1,56 @@
-use crate::tuple::{Color, Point};
+use crate::tuple::{Color, Point, Tuple};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Stripe {
-    pub a: Color,
-    pub b: Color,
+    pub palette: Vec<Color>,
+    pub softness: f64,
 }
 
 impl Stripe {
     pub fn new(a: Color, b: Color) -> Stripe {
-        Stripe { a, b }
+        Stripe::palette(vec![a, b])
+    }
+
+    // Stripes cycle through the colors in order, one per unit of x.
+    pub fn palette(palette: Vec<Color>) -> Stripe {
+        assert!(!palette.is_empty(), "a stripe palette needs at least one color");
+        Stripe { palette, softness: 0.0 }
+    }
+
+    pub fn with_softness(mut self, softness: f64) -> Stripe {
+        self.softness = softness;
+        self
     }
 
     pub fn stripe_at(&self, point: Point) -> Color {
-        if point.0.floor() as i32 % 2 == 0 {
-            self.a
-        } else {
-            self.b
-        }
+        let len = self.palette.len() as i64;
+        soft_cells(point.0, self.softness)
+            .iter()
+            .fold(Tuple::color(0.0, 0.0, 0.0), |acc, &(cell, weight)| {
+                acc + self.palette[cell.rem_euclid(len) as usize] * weight
+            })
+    }
+}
+
+// The unit cells covering coord and how much each contributes. Within
+// softness of a boundary the weight ramps linearly across it, so the two
+// cells meet at 50/50 on the boundary itself; elsewhere one cell has it all.
+pub(crate) fn soft_cells(coord: f64, softness: f64) -> [(i64, f64); 2] {
+    let cell = coord.floor() as i64;
+    let softness = softness.clamp(0.0, 0.5);
+    if softness == 0.0 {
+        return [(cell, 1.0), (cell, 0.0)];
+    }
+
+    let f = coord - coord.floor();
+    if f < softness {
+        let w = 0.5 + f / (2.0 * softness);
+        [(cell, w), (cell - 1, 1.0 - w)]
+    } else if f > 1.0 - softness {
+        let w = 0.5 + (1.0 - f) / (2.0 * softness);
+        [(cell, w), (cell + 1, 1.0 - w)]
+    } else {
+        [(cell, 1.0), (cell, 0.0)]
     }
 }
 
@@ -86,4 +120,50 @@ mod tests {
         let c = pattern.pattern_at_shape(&object, Tuple::point(1.5, 0.0, 0.0));
         assert_eq!(c, Color::white());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn a_three_color_stripe_cycles_through_its_palette() {
+        let pattern = Pattern::stripe_palette(vec![Color::red(), Color::green(), Color::blue()]);
+        assert_eq!(pattern.pattern_at(Tuple::point(0.5, 0.0, 0.0)), Color::red());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.5, 0.0, 0.0)), Color::green());
+        assert_eq!(pattern.pattern_at(Tuple::point(2.5, 0.0, 0.0)), Color::blue());
+        assert_eq!(pattern.pattern_at(Tuple::point(3.5, 0.0, 0.0)), Color::red());
+        assert_eq!(pattern.pattern_at(Tuple::point(-0.5, 0.0, 0.0)), Color::blue());
+    }
+
+    #[test]
+    fn a_two_color_stripe_is_a_two_color_palette() {
+        let pattern = Pattern::stripe(Color::white(), Color::black());
+        let palette = Pattern::stripe_palette(vec![Color::white(), Color::black()]);
+        assert_eq!(pattern, palette);
+        for x in [-2.5_f64, -1.5, -1.0, -0.1, 0.0, 0.9, 1.0, 2.3] {
+            let expected = if x.floor() as i32 % 2 == 0 { Color::white() } else { Color::black() };
+            assert_eq!(pattern.pattern_at(Tuple::point(x, 0.0, 0.0)), expected);
+        }
+    }
+
+    #[test]
+    fn zero_softness_keeps_the_hard_stripe() {
+        let hard = Pattern::stripe(Color::white(), Color::black());
+        let soft = hard.with_softness(0.0);
+        for x in [-1.1, -0.1, 0.0, 0.5, 0.9, 0.999, 1.0, 1.001, 2.3] {
+            assert_eq!(soft.pattern_at(Tuple::point(x, 0.0, 0.0)), hard.pattern_at(Tuple::point(x, 0.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn softness_blends_colors_near_a_stripe_boundary() {
+        let pattern = Pattern::stripe(Color::white(), Color::black()).with_softness(0.1);
+        let epsilon = 1e-10;
+        assert_eq!(pattern.pattern_at(Tuple::point(0.5, 0.0, 0.0)), Color::white());
+        assert_eq!(pattern.pattern_at(Tuple::point(1.5, 0.0, 0.0)), Color::black());
+
+        let c = pattern.pattern_at(Tuple::point(1.0, 0.0, 0.0));
+        assert!((c.0 - 0.5).abs() < epsilon && (c.1 - 0.5).abs() < epsilon && (c.2 - 0.5).abs() < epsilon);
+
+        let before = pattern.pattern_at(Tuple::point(0.95, 0.0, 0.0));
+        let after = pattern.pattern_at(Tuple::point(1.05, 0.0, 0.0));
+        assert!((before.0 - 0.75).abs() < epsilon);
+        assert!((after.0 - 0.25).abs() < epsilon);
+    }
+}
@@ -65,4 +65,22 @@ mod tests {
         assert_eq!(r2.origin, Tuple::point(2.0, 6.0, 12.0));
         assert_eq!(r2.direction, Tuple::vector(0.0, 3.0, 0.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn transforming_a_ray_twice_matches_the_composed_matrix() {
+        let r = Ray::new(Tuple::point(1.0, 2.0, 3.0), Tuple::vector(0.0, 1.0, 0.0));
+        let a = Matrix::translation(3.0, -4.0, 5.0);
+        let b = Matrix::rotation_x(std::f64::consts::PI / 3.0);
+
+        let stepwise = r.transform(&a).transform(&b);
+        let composed = r.transform(&(&b * &a));
+
+        let epsilon = 1e-10;
+        for (x, y) in stepwise.origin.as_array().iter().zip(composed.origin.as_array().iter()) {
+            assert!((x - y).abs() < epsilon);
+        }
+        for (x, y) in stepwise.direction.as_array().iter().zip(composed.direction.as_array().iter()) {
+            assert!((x - y).abs() < epsilon);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub struct RefractiveIndex;
+
+impl RefractiveIndex {
+    pub const VACUUM: f64 = 1.0;
+    pub const WATER: f64 = 1.333;
+    pub const GLASS: f64 = 1.52;
+    pub const DIAMOND: f64 = 2.417;
+}
@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderStats {
+    pub primary_rays: usize,
+    pub shadow_rays: usize,
+    pub reflection_rays: usize,
+    pub refraction_rays: usize,
+    pub intersection_tests: usize,
+}
+
+// One set per render, handed down through the tracing calls, so renders
+// running side by side never share counts. Tracing without counters costs
+// nothing beyond a None check.
+#[derive(Debug, Default)]
+pub struct RenderCounters {
+    primary_rays: AtomicUsize,
+    shadow_rays: AtomicUsize,
+    reflection_rays: AtomicUsize,
+    refraction_rays: AtomicUsize,
+    intersection_tests: AtomicUsize,
+}
+
+impl RenderCounters {
+    pub fn new() -> RenderCounters {
+        RenderCounters::default()
+    }
+
+    pub fn reset(&self) {
+        self.primary_rays.store(0, Ordering::Relaxed);
+        self.shadow_rays.store(0, Ordering::Relaxed);
+        self.reflection_rays.store(0, Ordering::Relaxed);
+        self.refraction_rays.store(0, Ordering::Relaxed);
+        self.intersection_tests.store(0, Ordering::Relaxed);
+    }
+
+    pub fn add_primary_ray(&self) {
+        self.add(&self.primary_rays, 1);
+    }
+
+    pub fn add_shadow_ray(&self) {
+        self.add(&self.shadow_rays, 1);
+    }
+
+    pub fn add_reflection_ray(&self) {
+        self.add(&self.reflection_rays, 1);
+    }
+
+    pub fn add_refraction_ray(&self) {
+        self.add(&self.refraction_rays, 1);
+    }
+
+    pub fn add_intersection_tests(&self, n: usize) {
+        self.add(&self.intersection_tests, n);
+    }
+
+    fn add(&self, counter: &AtomicUsize, n: usize) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> RenderStats {
+        RenderStats {
+            primary_rays: self.primary_rays.load(Ordering::Relaxed),
+            shadow_rays: self.shadow_rays.load(Ordering::Relaxed),
+            reflection_rays: self.reflection_rays.load(Ordering::Relaxed),
+            refraction_rays: self.refraction_rays.load(Ordering::Relaxed),
+            intersection_tests: self.intersection_tests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_add_up_events_until_reset() {
+        let counters = RenderCounters::new();
+        assert_eq!(counters.snapshot(), RenderStats::default());
+
+        counters.add_primary_ray();
+        counters.add_intersection_tests(3);
+        assert_eq!(counters.snapshot().primary_rays, 1);
+        assert_eq!(counters.snapshot().intersection_tests, 3);
+
+        counters.reset();
+        assert_eq!(counters.snapshot(), RenderStats::default());
+    }
+}
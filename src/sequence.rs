@@ -0,0 +1,85 @@
+// Sources of jitter in [0, 1). Anything that samples (soft shadows, pixel
+// jitter) pulls from a Sequence so a render can be reproduced from its seed.
+pub trait Sequence {
+    fn next(&mut self) -> f64;
+}
+
+// Always the middle of the range: the default for renders that should not
+// change between runs or when no seed is given.
+pub struct CenteredSequence;
+
+impl Sequence for CenteredSequence {
+    fn next(&mut self) -> f64 {
+        0.5
+    }
+}
+
+// SplitMix64, which is small, fast and good enough for sampling.
+pub struct RandomSequence {
+    state: u64,
+}
+
+impl RandomSequence {
+    pub fn new(seed: u64) -> RandomSequence {
+        RandomSequence { state: seed }
+    }
+}
+
+impl Sequence for RandomSequence {
+    fn next(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// Seed for one pixel's sequence, mixed from its coordinates and the camera
+// seed. It depends on nothing else, so a pixel draws the same samples
+// whichever thread renders it and in whatever order.
+pub fn pixel_seed(px: usize, py: usize, seed: u64) -> u64 {
+    let mut h = seed;
+    for v in [px as u64, py as u64] {
+        h = (h ^ v).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        h ^= h >> 31;
+    }
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_centered_sequence_always_returns_one_half() {
+        let mut seq = CenteredSequence;
+        assert_eq!(seq.next(), 0.5);
+        assert_eq!(seq.next(), 0.5);
+    }
+
+    #[test]
+    fn a_random_sequence_is_reproducible_from_its_seed() {
+        let mut a = RandomSequence::new(42);
+        let mut b = RandomSequence::new(42);
+        let mut c = RandomSequence::new(43);
+        let xs: Vec<f64> = (0..8).map(|_| a.next()).collect();
+        let ys: Vec<f64> = (0..8).map(|_| b.next()).collect();
+        let zs: Vec<f64> = (0..8).map(|_| c.next()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+        assert!(xs.iter().all(|x| (0.0..1.0).contains(x)));
+    }
+
+    #[test]
+    fn pixel_seeds_are_stable_and_distinct() {
+        assert_eq!(pixel_seed(3, 4, 7), pixel_seed(3, 4, 7));
+        assert_ne!(pixel_seed(3, 4, 7), pixel_seed(4, 3, 7));
+        assert_ne!(pixel_seed(3, 4, 7), pixel_seed(3, 4, 8));
+        assert_ne!(pixel_seed(0, 0, 0), pixel_seed(1, 0, 0));
+        assert_ne!(pixel_seed(0, 0, 0), 0);
+    }
+}
@@ -1,11 +1,17 @@
+use crate::bounds::Bounds;
+use crate::intersection::Intersection;
+use crate::object::Object;
+use crate::patterns::mapping::PatternMapping;
 use crate::ray::Ray;
 use crate::shapes::cone::Cone;
 use crate::shapes::cube::Cube;
 use crate::shapes::cylinder::Cylinder;
+use crate::shapes::group::Group;
+use crate::shapes::instance::Instance;
 use crate::shapes::plane::Plane;
 use crate::shapes::sphere::Sphere;
 use crate::shapes::test_shape::TestShape;
-use crate::tuple::{Point, Vector};
+use crate::tuple::{Point, Tuple, Vector};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Shape {
@@ -15,18 +21,41 @@ pub enum Shape {
     Cube(Cube),
     Cylinder(Cylinder),
     Cone(Cone),
+    Group(Group),
+    Instance(Instance),
 }
 
 impl Shape {
-    pub fn local_intersect(&self, local_ray: &Ray) -> Vec<f64> {
-        match self {
-            Shape::TestShape(s) => s.local_intersect(&local_ray),
-            Shape::Sphere(s) => s.local_intersect(&local_ray),
-            Shape::Plane(s) => s.local_intersect(&local_ray),
-            Shape::Cube(s) => s.local_intersect(&local_ray),
-            Shape::Cylinder(s) => s.local_intersect(&local_ray),
-            Shape::Cone(s) => s.local_intersect(&local_ray),
-        }
+    // Every shape comes back as intersections borrowing the object it belongs
+    // to. Leaf shapes only compute distances; groups hand back their
+    // children's intersections as they are.
+    pub fn local_intersect<'a>(&'a self, object: &'a Object, local_ray: &Ray) -> Vec<Intersection<'a>> {
+        let mut xs = Vec::new();
+        self.local_intersect_into(object, local_ray, &mut xs);
+        xs
+    }
+
+    // Appends to the caller's list rather than building one per object.
+    pub fn local_intersect_into<'a>(&'a self, object: &'a Object, local_ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        let ts = match self {
+            // Spheres are the bulk of most scenes; their roots stay on the
+            // stack and go straight into the caller's list.
+            Shape::Sphere(s) => {
+                if let Some((t1, t2)) = s.roots(local_ray) {
+                    xs.push(Intersection::new(t1, object));
+                    xs.push(Intersection::new(t2, object));
+                }
+                return;
+            }
+            Shape::Group(g) => return xs.append(&mut g.local_intersect(local_ray)),
+            Shape::Instance(s) => return xs.append(&mut s.local_intersect(object, local_ray)),
+            Shape::TestShape(s) => s.local_intersect(local_ray),
+            Shape::Plane(s) => s.local_intersect(local_ray),
+            Shape::Cube(s) => s.local_intersect(local_ray),
+            Shape::Cylinder(s) => s.local_intersect(local_ray),
+            Shape::Cone(s) => s.local_intersect(local_ray),
+        };
+        xs.extend(ts.into_iter().map(|t| Intersection::new(t, object)));
     }
 
     pub fn local_normal_at(&self, local_point: &Point) -> Vector {
@@ -37,6 +66,61 @@ impl Shape {
             Shape::Cube(s) => s.local_normal_at(&local_point),
             Shape::Cylinder(s) => s.local_normal_at(&local_point),
             Shape::Cone(s) => s.local_normal_at(&local_point),
+            Shape::Group(_) => panic!("Groups have no normal of their own"),
+            Shape::Instance(s) => s.local_normal_at(local_point),
         }
     }
-}
\ No newline at end of file
+
+    // Like local_normal_at, but never degenerate, so it is safe to normalize
+    // for shading.
+    pub fn shading_normal_at(&self, local_point: &Point) -> Vector {
+        match self {
+            Shape::Cone(s) => s.shading_normal_at(local_point),
+            _ => self.local_normal_at(local_point),
+        }
+    }
+
+    // The uv mapping that suits the shape, for when no pattern picks one.
+    pub fn default_mapping(&self) -> PatternMapping {
+        match self {
+            Shape::Sphere(_) => PatternMapping::Spherical,
+            Shape::Cube(_) => PatternMapping::Cubic,
+            Shape::Cylinder(_) | Shape::Cone(_) => PatternMapping::Cylindrical,
+            Shape::TestShape(_) | Shape::Plane(_) | Shape::Group(_) => PatternMapping::Planar,
+            Shape::Instance(s) => s.get_geometry().get_shape().default_mapping(),
+        }
+    }
+
+    // Bounds in object space. Groups report the union of their children's
+    // bounds in the group's own space.
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            Shape::TestShape(_) | Shape::Sphere(_) | Shape::Cube(_) => {
+                Bounds::new(Tuple::point(-1.0, -1.0, -1.0), Tuple::point(1.0, 1.0, 1.0))
+            }
+            Shape::Plane(s) => {
+                let r = s.radius.unwrap_or(f64::INFINITY);
+                Bounds::new(Tuple::point(-r, 0.0, -r), Tuple::point(r, 0.0, r))
+            }
+            Shape::Cylinder(s) => Bounds::new(
+                s.axis.swap_with_y(Tuple::point(-1.0, s.minimum, -1.0)),
+                s.axis.swap_with_y(Tuple::point(1.0, s.maximum, 1.0)),
+            ),
+            Shape::Cone(s) => {
+                let r = s.minimum.abs().max(s.maximum.abs());
+                Bounds::new(
+                    s.axis.swap_with_y(Tuple::point(-r, s.minimum, -r)),
+                    s.axis.swap_with_y(Tuple::point(r, s.maximum, r)),
+                )
+            }
+            Shape::Group(g) => {
+                let mut bounds = Bounds::empty();
+                for child in g.get_children() {
+                    bounds.merge(&child.bounds());
+                }
+                bounds
+            }
+            Shape::Instance(s) => s.get_geometry().bounds(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{ray::Ray, tuple::Tuple};
+use crate::{axis::Axis, ray::Ray, tuple::Tuple};
 
 
 #[derive(Debug, Clone, PartialEq)]
@@ -6,6 +6,7 @@ pub struct Cone {
     pub minimum: f64,
     pub maximum: f64,
     pub closed: bool,
+    pub axis: Axis,
 }
 
 
@@ -16,27 +17,49 @@ impl Cone {
             minimum: f64::NEG_INFINITY,
             maximum: f64::INFINITY,
             closed: false,
+            axis: Axis::Y,
         }
     }
 
+    pub fn with_axis(mut self, axis: Axis) -> Cone {
+        self.axis = axis;
+        self
+    }
+
+    // The math below is written for a y-aligned shape; other axes are
+    // swapped into place on the way in and back on the way out.
     pub fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let ray = Ray::new(self.axis.swap_with_y(ray.origin), self.axis.swap_with_y(ray.direction));
+        self.intersect_along_y(&ray)
+    }
+
+    pub fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+        self.axis.swap_with_y(self.normal_along_y(&self.axis.swap_with_y(*local_point)))
+    }
+
+    fn intersect_along_y(&self, ray: &Ray) -> Vec<f64> {
         let a = ray.direction.0.powi(2) - ray.direction.1.powi(2) + ray.direction.2.powi(2);
         let b = 2.0 * ray.origin.0 * ray.direction.0 - 2.0 * ray.origin.1 * ray.direction.1 + 2.0 * ray.origin.2 * ray.direction.2;
         let c = ray.origin.0.powi(2) - ray.origin.1.powi(2) + ray.origin.2.powi(2);
 
         let mut xs = vec![];
 
-        if a.abs() < 1e-6 && b.abs() > 1e-6 {
-            xs.push(-c / (2.0 * b));
-        } else if a.abs() >= 1e-6 {
+        let parallel = a.abs() <= 1e-12 * ray.direction.dot(ray.direction);
+        if parallel && b.abs() > 1e-6 {
+            // With a gone the equation is b*t + c = 0. The book divides by 2b
+            // here, which lands halfway to the surface.
+            xs.push(-c / b);
+        } else if !parallel {
             let disc = b.powi(2) - 4.0 * a * c;
             if disc < 0.0 {
                 return xs;
             }
-    
-            let mut t0 = (-b - disc.sqrt()) / (2.0 * a);
-            let mut t1 = (-b + disc.sqrt()) / (2.0 * a);
-    
+
+            // Computing one root from the other avoids cancellation when `a`
+            // is tiny and the textbook formula subtracts nearly equal values.
+            let q = -0.5 * (b + b.signum() * disc.sqrt());
+            let (mut t0, mut t1) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+
             if t0 > t1 {
                 (t0, t1) = (t1, t0);
             } 
@@ -56,7 +79,7 @@ impl Cone {
         xs
     }
 
-    pub fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+    fn normal_along_y(&self, local_point: &Tuple) -> Tuple {
         let dist = local_point.0.powi(2) + local_point.2.powi(2);
         if dist < 1.0 && local_point.1 >= self.maximum - 1e-6 {
             Tuple::vector(0.0, 1.0, 0.0)
@@ -71,6 +94,17 @@ impl Cone {
         }
     }
 
+    // The surface normal vanishes at the apex, and normalizing it would turn
+    // shading to NaN, so the tip gets a straight-up normal instead.
+    pub fn shading_normal_at(&self, local_point: &Tuple) -> Tuple {
+        let n = self.local_normal_at(local_point);
+        if n.magnitude() < 1e-12 {
+            self.axis.swap_with_y(Tuple::vector(0.0, 1.0, 0.0))
+        } else {
+            n
+        }
+    }
+
     fn check_cap(&self, ray: &Ray, t: f64, r: f64) -> bool {
         let x = ray.origin.0 + t * ray.direction.0;
         let z = ray.origin.2 + t * ray.direction.2;
@@ -98,7 +132,7 @@ impl Cone {
 mod tests {
     use super::*;
 
-    use crate::ray::Ray;
+    use crate::{light::Light, object::Object, ray::Ray, world::World};
 
     #[test]
     fn intersecting_cone_with_ray() {
@@ -130,8 +164,9 @@ mod tests {
         let r = Ray::new(Tuple::point(0.0, 0.0, -1.0), Tuple::vector(0.0, 1.0, 1.0).normalize());
         let xs = cone.local_intersect(&r);
         assert_eq!(xs.len(), 1);
+        // The book expects 0.35355, half the true distance to the surface.
         let delta = 1e-4;
-        assert!((xs[0] - 0.35355).abs() < delta);
+        assert!((xs[0] - 0.70711).abs() < delta);
     }
 
     #[test]
@@ -140,6 +175,7 @@ mod tests {
             minimum: -0.5,
             maximum: 0.5,
             closed: true,
+            axis: Axis::Y,
         };
         let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 1.0, 0.0).normalize());
         let xs = cone.local_intersect(&r);
@@ -167,4 +203,47 @@ mod tests {
         assert_eq!(n, Tuple::vector(-1.0, 1.0, 0.0));
     }
 
+    #[test]
+    fn the_shading_normal_at_the_apex_is_defined() {
+        let cone = Cone::new();
+        let n = cone.shading_normal_at(&Tuple::point(0.0, 0.0, 0.0));
+        assert_eq!(n, Tuple::vector(0.0, 1.0, 0.0));
+
+        let n = cone.shading_normal_at(&Tuple::point(1.0, 1.0, 1.0));
+        assert_eq!(n, cone.local_normal_at(&Tuple::point(1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn shading_a_ray_that_hits_the_apex_gives_a_finite_color() {
+        let light = Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let w = World::new().with_objects(vec![Object::cone()]).with_lights(vec![light]);
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let c = w.color_at(&r, 4);
+        assert!(c.0.is_finite() && c.1.is_finite() && c.2.is_finite());
+    }
+
+    #[test]
+    fn a_z_axis_cone_opens_along_z() {
+        let cone = Cone::new().with_axis(Axis::Z);
+        let r = Ray::new(Tuple::point(0.0, -5.0, 1.0), Tuple::vector(0.0, 1.0, 0.0));
+        let xs = cone.local_intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0] - 4.0).abs() < 1e-10);
+        assert!((xs[1] - 6.0).abs() < 1e-10);
+        assert_eq!(cone.local_normal_at(&Tuple::point(0.0, 1.0, 1.0)), Tuple::vector(0.0, 1.0, -1.0));
+    }
+
+    #[test]
+    fn a_nearly_parallel_ray_keeps_its_near_cone_hit() {
+        let cone = Cone::new();
+        for i in -10..=10 {
+            let tilt = i as f64 * 1e-7;
+            let r = Ray::new(Tuple::point(0.0, 0.0, -1.0), Tuple::vector(0.0, 1.0, 1.0 + tilt).normalize());
+            let xs = cone.local_intersect(&r);
+            assert!(!xs.is_empty());
+            let near = xs.iter().copied().filter(|t| t.abs() < 1.0).collect::<Vec<_>>();
+            assert_eq!(near.len(), 1);
+            assert!((near[0] - 0.70711).abs() < 1e-4);
+        }
+    }
 }
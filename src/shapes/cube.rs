@@ -16,7 +16,10 @@ impl Cube {
         let tmin = xtmin.max(ytmin).max(ztmin);
         let tmax = xtmax.min(ytmax).min(ztmax);
 
-        if tmin > tmax {
+        // Intersections are always strictly increasing. A ray that only touches
+        // an edge or corner (tmin == tmax) counts as a miss, so CSG never sees
+        // a zero-length span.
+        if tmin >= tmax {
             return vec![];
         }
 
@@ -41,8 +44,12 @@ impl Cube {
 
         let (tmin, tmax) = if direction.abs() >= 1e-6 {
             (tmin_numerator / direction, tmax_numerator / direction)
+        } else if (-1.0..=1.0).contains(&origin) {
+            // Parallel to the slab: inside it everywhere or outside it everywhere.
+            // Multiplying a zero numerator by infinity would give NaN here.
+            (f64::NEG_INFINITY, f64::INFINITY)
         } else {
-            (tmin_numerator * f64::INFINITY, tmax_numerator * f64::INFINITY)
+            return (f64::INFINITY, f64::NEG_INFINITY);
         };
 
         if tmin > tmax {
@@ -55,7 +62,7 @@ impl Cube {
 
 #[cfg(test)]
 mod tests {
-    use crate::{ray::Ray, tuple::Tuple};
+    use crate::{matrix::Matrix, object::Object, ray::Ray, shape::Shape, transformation::Transformation, tuple::Tuple};
 
     use super::*;
 
@@ -83,4 +90,63 @@ mod tests {
         let n = c.local_normal_at(&Tuple::point(1.0, 0.5, -0.8));
         assert_eq!(n, Tuple::vector(1.0, 0.0, 0.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn a_ray_through_an_edge_gives_sorted_intersections() {
+        let c = Cube::new();
+        let r = Ray::new(Tuple::point(2.0, 2.0, 0.0), Tuple::vector(-1.0, -1.0, 0.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs, vec![1.0, 3.0]);
+
+        let r = Ray::new(Tuple::point(2.0, 0.0, 0.0), Tuple::vector(-1.0, 1.0, 0.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs.len(), 0);
+    }
+
+    #[test]
+    fn a_ray_through_a_corner_gives_sorted_intersections() {
+        let c = Cube::new();
+        let r = Ray::new(Tuple::point(2.0, 2.0, 2.0), Tuple::vector(-1.0, -1.0, -1.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs, vec![1.0, 3.0]);
+
+        let r = Ray::new(Tuple::point(2.0, 0.0, 0.0), Tuple::vector(-1.0, 1.0, 1.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs.len(), 0);
+    }
+
+    #[test]
+    fn a_ray_along_a_face_does_not_produce_nan() {
+        let c = Cube::new();
+        let r = Ray::new(Tuple::point(-5.0, 1.0, 0.0), Tuple::vector(1.0, 0.0, 0.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs, vec![4.0, 6.0]);
+
+        let r = Ray::new(Tuple::point(-5.0, 1.5, 0.0), Tuple::vector(1.0, 0.0, 0.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs.len(), 0);
+    }
+
+    #[test]
+    fn a_transformed_cube_object_intersects_through_its_object_transform() {
+        let cube = Object::cube().with_transform(Matrix::identity().scale(2.0, 1.0, 1.0).translate(5.0, 0.0, 0.0));
+        assert_eq!(*cube.get_shape(), Shape::Cube(Cube::new()));
+        let r = Ray::new(Tuple::point(0.0, 0.5, 0.0), Tuple::vector(1.0, 0.0, 0.0));
+        let xs = cube.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 3.0);
+        assert_eq!(xs[1].t, 7.0);
+    }
+
+    #[test]
+    fn cube_intersections_refer_to_the_cube_object() {
+        let cube = Object::cube();
+        let r = Ray::new(Tuple::point(0.0, 0.5, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = cube.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        for i in &xs {
+            assert!(std::ptr::eq(i.object, &cube));
+            assert_eq!(i.object.id, cube.id);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{ray::Ray, tuple::Tuple};
+use crate::{axis::Axis, ray::Ray, tuple::Tuple};
 
 
 #[derive(Debug, Clone, PartialEq)]
@@ -6,6 +6,7 @@ pub struct Cylinder {
     pub minimum: f64,
     pub maximum: f64,
     pub closed: bool,
+    pub axis: Axis,
 }
 
 
@@ -16,13 +17,32 @@ impl Cylinder {
             minimum: f64::NEG_INFINITY,
             maximum: f64::INFINITY,
             closed: false,
+            axis: Axis::Y,
         }
     }
 
+    pub fn with_axis(mut self, axis: Axis) -> Cylinder {
+        self.axis = axis;
+        self
+    }
+
+    // The math below is written for a y-aligned shape; other axes are
+    // swapped into place on the way in and back on the way out.
     pub fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let ray = Ray::new(self.axis.swap_with_y(ray.origin), self.axis.swap_with_y(ray.direction));
+        self.intersect_along_y(&ray)
+    }
+
+    pub fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+        self.axis.swap_with_y(self.normal_along_y(&self.axis.swap_with_y(*local_point)))
+    }
+
+    fn intersect_along_y(&self, ray: &Ray) -> Vec<f64> {
         let mut xs = vec![];
         let a = ray.direction.0.powi(2) + ray.direction.2.powi(2);
-        if a.abs() > 1e-6 {
+        // Scale the parallel test by the direction's length so a slightly
+        // tilted ray still solves the quadratic instead of dropping its hits.
+        if a > 1e-12 * ray.direction.dot(ray.direction) {
             let b = 2.0 * ray.origin.0 * ray.direction.0 + 2.0 * ray.origin.2 * ray.direction.2;
             let c = ray.origin.0.powf(2.) + ray.origin.2.powf(2.) - 1.0;
 
@@ -49,7 +69,7 @@ impl Cylinder {
         xs
     }
 
-    pub fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+    fn normal_along_y(&self, local_point: &Tuple) -> Tuple {
         let dist = local_point.0.powi(2) + local_point.2.powi(2);
         if dist < 1.0 && local_point.1 >= self.maximum - 1e-6 {
             Tuple::vector(0.0, 1.0, 0.0)
@@ -157,6 +177,7 @@ mod tests {
             minimum: 1.0,
             maximum: 2.0,
             closed: true,
+            axis: Axis::Y,
         };
 
         let r = Ray::new(Tuple::point(0.0, 1.5, 0.0), Tuple::vector(0.1, 1.0, 0.0).normalize());
@@ -196,6 +217,7 @@ mod tests {
             minimum: 1.0,
             maximum: 2.0,
             closed: true,
+            axis: Axis::Y,
         };
 
         let r = Ray::new(Tuple::point(0.0, 3.0, 0.0), Tuple::vector(0.0, -1.0, 0.0));
@@ -225,6 +247,7 @@ mod tests {
             minimum: 1.0,
             maximum: 2.0,
             closed: true,
+            axis: Axis::Y,
         };
 
         let n = c.local_normal_at(&Tuple::point(0.0, 1.0, 0.0));
@@ -246,4 +269,50 @@ mod tests {
         assert_eq!(n, Tuple::vector(0.0, 1.0, 0.0));
     }
 
+    #[test]
+    fn an_x_axis_cylinder_is_hit_by_a_ray_along_y() {
+        let c = Cylinder::new().with_axis(Axis::X);
+        let r = Ray::new(Tuple::point(0.0, -5.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs, vec![4.0, 6.0]);
+
+        // A ray along the x axis runs down the middle and never meets the side.
+        let r = Ray::new(Tuple::point(-5.0, 0.0, 0.0), Tuple::vector(1.0, 0.0, 0.0));
+        assert!(c.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn the_normal_on_an_x_axis_cylinder_is_perpendicular_to_x() {
+        let c = Cylinder::new().with_axis(Axis::X);
+        assert_eq!(c.local_normal_at(&Tuple::point(3.0, 1.0, 0.0)), Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(c.local_normal_at(&Tuple::point(-2.0, 0.0, -1.0)), Tuple::vector(0.0, 0.0, -1.0));
+        assert_eq!(c.local_normal_at(&Tuple::point(0.5, -1.0, 0.0)), Tuple::vector(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn the_caps_of_a_closed_z_axis_cylinder_face_along_z() {
+        let mut c = Cylinder::new().with_axis(Axis::Z);
+        c.minimum = 1.0;
+        c.maximum = 2.0;
+        c.closed = true;
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(c.local_intersect(&r).len(), 2);
+        assert_eq!(c.local_normal_at(&Tuple::point(0.5, 0.0, 2.0)), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(c.local_normal_at(&Tuple::point(0.0, 0.5, 1.0)), Tuple::vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn a_nearly_parallel_ray_hits_a_cylinder_consistently() {
+        let c = Cylinder::new();
+        for i in 0..=20 {
+            let tilt = 0.5e-3 + i as f64 * 0.05e-3;
+            let r = Ray::new(Tuple::point(0.5, 0.0, 0.0), Tuple::vector(tilt, 1.0, 0.0).normalize());
+            let xs = c.local_intersect(&r);
+            assert_eq!(xs.len(), 2);
+            for t in xs {
+                let p = r.position(t);
+                assert!((p.0.powi(2) + p.2.powi(2) - 1.0).abs() < 1e-6);
+            }
+        }
+    }
 }
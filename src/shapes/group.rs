@@ -0,0 +1,158 @@
+use crate::{intersection::Intersection, intersections::Intersections, object::Object, ray::Ray};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Group {
+    children: Vec<Object>,
+}
+
+impl Group {
+    pub fn new() -> Group {
+        Group {
+            children: vec![],
+        }
+    }
+
+    // The ray is already in group space; each child applies its own transform.
+    pub fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let mut xs: Vec<Intersection> = vec![];
+        for child in &self.children {
+            child.intersect_into(ray, &mut xs);
+        }
+        xs.sort_by_t();
+        xs
+    }
+
+    pub fn get_children(&self) -> &Vec<Object> {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<Object> {
+        &mut self.children
+    }
+
+    pub fn add_child(&mut self, child: Object) {
+        self.children.push(child);
+    }
+
+    pub fn add_children(&mut self, children: Vec<Object>) {
+        self.children.extend(children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{material::Material, matrix::Matrix, ray::Ray, shape::Shape, transformation::Transformation, tuple::Tuple};
+
+    use super::*;
+
+    #[test]
+    fn creating_a_new_group() {
+        let g = Object::group();
+        assert!(matches!(g.get_shape(), Shape::Group(_)));
+        assert_eq!(*g.get_transform(), Matrix::identity());
+        assert_eq!(g.get_children().len(), 0);
+    }
+
+    #[test]
+    fn adding_a_child_to_a_group() {
+        let mut g = Object::group();
+        let s = Object::test_shape();
+        g.add_child(s.clone());
+        assert_eq!(g.get_children().len(), 1);
+        assert_eq!(g.get_children()[0], s);
+    }
+
+    #[test]
+    fn adding_children_in_bulk_matches_adding_them_one_at_a_time() {
+        let spheres: Vec<Object> = (0..10)
+            .map(|i| Object::sphere().with_transform(Matrix::translation(i as f64 * 3.0, 0.0, 0.0)))
+            .collect();
+
+        let mut one_at_a_time = Object::group().with_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        for s in &spheres {
+            one_at_a_time.add_child(s.clone());
+        }
+
+        let mut bulk = Object::group().with_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        bulk.add_children(spheres.clone());
+
+        assert_eq!(bulk.get_children().len(), 10);
+        assert_eq!(bulk, one_at_a_time);
+        for (child, s) in bulk.get_children().iter().zip(&spheres) {
+            assert_eq!(child.id, s.id);
+        }
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_an_empty_group() {
+        let g = Object::group();
+        let r = Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 0);
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_nonempty_group() {
+        let mut g = Object::group();
+        let s1 = Object::sphere();
+        let s2 = Object::sphere().with_transform(Matrix::translation(0.,0.,-3.));
+        let s3 = Object::sphere().with_transform(Matrix::translation(5.0, 0.0, 0.0));
+        g.add_child(s1.clone());
+        g.add_child(s2.clone());
+        g.add_child(s3.clone());
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 4);
+        assert_eq!(xs[0].object, &s2);
+        assert_eq!(xs[1].object, &s2);
+        assert_eq!(xs[2].object, &s1);
+        assert_eq!(xs[3].object, &s1);
+    }
+
+    #[test]
+    fn intersecting_a_transformed_group() {
+        let mut g = Object::group();
+        g.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        let s = Object::sphere().with_transform(Matrix::translation(5.0, 0.0, 0.0));
+        g.add_child(s.clone());
+        let r = Ray::new(Tuple::point(10.0, 0.0, -10.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 2);
+    }
+
+    #[test]
+    fn only_groups_have_children() {
+        let mut s = Object::sphere();
+        assert!(s.children_mut().is_none());
+        assert_eq!(s.get_children().len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only groups can have children")]
+    fn adding_a_child_to_a_non_group_panics() {
+        let mut s = Object::sphere();
+        s.add_child(Object::cube());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only groups can have children")]
+    fn adding_children_to_a_non_group_panics() {
+        let mut s = Object::sphere();
+        s.add_children(vec![Object::cube(), Object::cone()]);
+    }
+
+    #[test]
+    fn mutating_a_child_through_children_mut_is_visible_when_intersecting() {
+        let mut g = Object::group();
+        g.add_child(Object::sphere());
+        let red = Material::new().with_color(Tuple::color(1.0, 0.0, 0.0));
+        g.children_mut().unwrap()[0].set_material(red.clone());
+        g.children_mut().unwrap()[0].set_transform(Matrix::translation(0.0, 0.0, 1.0));
+
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 5.0);
+        assert_eq!(*xs[0].object.get_material(), red);
+    }
+}
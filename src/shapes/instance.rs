@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use crate::{intersection::Intersection, object::Object, ray::Ray, tuple::{Point, Vector}};
+
+// A shared piece of geometry placed by the object that holds it. Cloning an
+// instance only bumps the Arc, so a forest of identical trees stores one tree.
+// Groups are refused: their hits land on the children, which know nothing of
+// the instance placing them.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Instance {
+    geometry: Arc<Object>,
+}
+
+impl Instance {
+    pub fn new(geometry: Arc<Object>) -> Instance {
+        Instance { geometry }
+    }
+
+    pub fn get_geometry(&self) -> &Arc<Object> {
+        &self.geometry
+    }
+
+    // The ray is in instance space; the geometry applies its own transform,
+    // and every hit is handed back on the instance so each one shades with
+    // its own placement and material.
+    pub fn local_intersect<'a>(&'a self, object: &'a Object, ray: &Ray) -> Vec<Intersection<'a>> {
+        self.geometry.intersect(ray).into_iter().map(|i| Intersection::new(i.t, object)).collect()
+    }
+
+    // Instance space is the geometry's world space.
+    pub fn local_normal_at(&self, point: &Point) -> Vector {
+        self.geometry.normal_at(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{color::Colors, material::Material, matrix::Matrix, transformation::Transformation, tuple::{Color, Tuple}};
+
+    use super::*;
+
+    #[test]
+    fn instances_of_one_sphere_intersect_independently() {
+        let tree = Arc::new(Object::sphere().with_material(Material::new().with_color(Color::green())));
+        let left = Object::instance(Arc::clone(&tree)).with_transform(Matrix::translation(-3.0, 0.0, 0.0));
+        let right = Object::instance(Arc::clone(&tree)).with_transform(Matrix::translation(3.0, 0.0, 0.0));
+
+        let r = Ray::new(Tuple::point(-3.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = left.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert_eq!(xs[0].object.id, left.id);
+        assert!(right.intersect(&r).is_empty());
+
+        let n = right.normal_at(&Tuple::point(2.0, 0.0, 0.0));
+        assert_eq!(n, Tuple::vector(-1.0, 0.0, 0.0));
+
+        assert_eq!(right.get_material().color, Color::green());
+        assert_eq!(Arc::strong_count(&tree), 3);
+    }
+
+    #[test]
+    fn editing_the_shared_material_reaches_every_instance() {
+        let tree = Arc::new(Object::sphere().with_material(Material::new().with_color(Color::green())));
+        let left = Object::instance(Arc::clone(&tree)).with_transform(Matrix::translation(-3.0, 0.0, 0.0));
+        let right = Object::instance(Arc::clone(&tree)).with_transform(Matrix::translation(3.0, 0.0, 0.0));
+
+        tree.update_material(|m| m.color = Color::orange());
+        assert_eq!(left.get_material().color, Color::orange());
+        assert_eq!(right.get_material().color, Color::orange());
+
+        // An instance can still be given a material of its own.
+        let red = right.with_material(Material::new().with_color(Color::red()));
+        assert_eq!(red.get_material().color, Color::red());
+        assert_eq!(left.get_material().color, Color::orange());
+    }
+
+    #[test]
+    #[should_panic(expected = "single shape")]
+    fn a_group_cannot_be_instanced() {
+        let mut forest = Object::group();
+        forest.add_child(Object::sphere());
+        Object::instance(Arc::new(forest));
+    }
+
+    #[test]
+    fn an_instance_applies_the_geometry_transform_inside_its_own() {
+        let egg = Arc::new(Object::sphere().with_transform(Matrix::scaling(1.0, 2.0, 1.0)));
+        let placed = Object::instance(egg).with_transform(Matrix::translation(0.0, 0.0, 10.0));
+
+        let r = Ray::new(Tuple::point(0.0, 1.5, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(placed.intersect(&r).len(), 2);
+        let b = placed.bounds();
+        assert_eq!(b.min, Tuple::point(-1.0, -2.0, 9.0));
+        assert_eq!(b.max, Tuple::point(1.0, 2.0, 11.0));
+    }
+}
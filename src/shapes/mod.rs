@@ -3,4 +3,6 @@ pub mod sphere;
 pub mod plane;
 pub mod cube;
 pub mod cylinder;
-pub mod cone;
\ No newline at end of file
+pub mod cone;
+pub mod group;
+pub mod instance;
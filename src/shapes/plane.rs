@@ -1,19 +1,55 @@
 use crate::{ray::Ray, tuple::Tuple};
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Plane;
+pub struct Plane {
+    pub parallel_epsilon: f64,
+    pub max_distance: f64,
+    pub radius: Option<f64>,
+}
 
 impl Plane {
     pub fn new() -> Plane {
-        Plane {}
+        Plane {
+            parallel_epsilon: 1e-5,
+            max_distance: 1e6,
+            radius: None,
+        }
+    }
+
+    pub fn with_parallel_epsilon(mut self, parallel_epsilon: f64) -> Plane {
+        self.parallel_epsilon = parallel_epsilon;
+        self
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f64) -> Plane {
+        self.max_distance = max_distance;
+        self
+    }
+
+    // A disc of this radius around the origin instead of an endless plane.
+    pub fn with_radius(mut self, radius: f64) -> Plane {
+        self.radius = Some(radius);
+        self
     }
 
     pub fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
-        if ray.direction.1.abs() < 1e-5 {
+        if ray.direction.1.abs() < self.parallel_epsilon {
             return vec![];
         }
 
+        // Nearly parallel rays meet the plane absurdly far away, which shows
+        // up as spurious hits along the horizon.
         let t = -ray.origin.1 / ray.direction.1;
+        if t.abs() > self.max_distance {
+            return vec![];
+        }
+        if let Some(radius) = self.radius {
+            let x = ray.origin.0 + t * ray.direction.0;
+            let z = ray.origin.2 + t * ray.direction.2;
+            if x * x + z * z > radius * radius {
+                return vec![];
+            }
+        }
         vec![t]
     }
 
@@ -27,7 +63,7 @@ mod tests {
 
     use super::*;
 
-    use crate::{object::Object, ray::Ray};
+    use crate::{object::Object, ray::Ray, shape::Shape};
 
     #[test]
     fn the_normal_of_a_plane_is_constant_everywhere() {
@@ -75,4 +111,49 @@ mod tests {
         assert_eq!(xs[0].t, 1.0);
         assert_eq!(xs[0].object, &p);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn a_nearly_parallel_ray_does_not_hit_the_plane_far_away() {
+        let p = Object::plane();
+        let r = Ray::new(Tuple::point(0.0, 100.0, 0.0), Tuple::vector(0.0, -2e-5, 1.0));
+        let xs = p.intersect(&r);
+        assert_eq!(xs.len(), 0);
+
+        let far = Object::new(Shape::Plane(Plane::new().with_max_distance(1e7)));
+        let xs = far.intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!((xs[0].t - 5e6).abs() < 1e-3);
+    }
+
+    #[test]
+    fn a_crossing_ray_is_unaffected_by_the_far_distance() {
+        let p = Object::plane();
+        let r = Ray::new(Tuple::point(0.0, 1.0, -1.0), Tuple::vector(0.0, -1.0, 1.0));
+        let xs = p.intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 1.0);
+    }
+
+    #[test]
+    fn the_parallel_epsilon_is_configurable() {
+        let p = Object::new(Shape::Plane(Plane::new().with_parallel_epsilon(0.1)));
+        let r = Ray::new(Tuple::point(0.0, 1.0, 0.0), Tuple::vector(0.0, -0.05, 1.0));
+        let xs = p.intersect(&r);
+        assert_eq!(xs.len(), 0);
+    }
+
+    #[test]
+    fn a_plane_with_a_radius_only_exists_within_it() {
+        let disc = Object::new(Shape::Plane(Plane::new().with_radius(2.0)));
+        let inside = Ray::new(Tuple::point(1.0, 1.0, 1.0), Tuple::vector(0.0, -1.0, 0.0));
+        let outside = Ray::new(Tuple::point(2.0, 1.0, 1.0), Tuple::vector(0.0, -1.0, 0.0));
+        let slanted = Ray::new(Tuple::point(0.0, 1.0, 0.0), Tuple::vector(3.0, -1.0, 0.0));
+        assert_eq!(disc.intersect(&inside).len(), 1);
+        assert_eq!(disc.intersect(&outside).len(), 0);
+        assert_eq!(disc.intersect(&slanted).len(), 0);
+
+        let p = Object::plane();
+        assert_eq!(p.intersect(&outside).len(), 1);
+        assert_eq!(p.intersect(&slanted).len(), 1);
+    }
+}
@@ -1,17 +1,25 @@
+use std::sync::Mutex;
+
 use crate::ray::Ray;
 use crate::tuple::{Point, Tuple};
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct TestShape {}
+// The saved ray sits behind a Mutex rather than a RefCell so objects stay Sync
+// for the parallel renderer.
+#[derive(Debug)]
+pub struct TestShape {
+    saved_ray: Mutex<Option<Ray>>,
+}
 
 impl TestShape {
     #[allow(dead_code)]
     pub fn new() -> Self {
         TestShape {
+            saved_ray: Mutex::new(None),
         }
     }
 
-    pub fn local_intersect(&self, _ray: &Ray) -> Vec<f64> {
+    pub fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        *self.saved_ray.lock().unwrap() = Some(ray.clone());
         vec![]
     }
 
@@ -19,4 +27,21 @@ impl TestShape {
         Tuple::vector(point.0, point.1, point.2)
     }
 
-}
\ No newline at end of file
+    pub fn saved_ray(&self) -> Option<Ray> {
+        self.saved_ray.lock().unwrap().clone()
+    }
+}
+
+impl Clone for TestShape {
+    fn clone(&self) -> Self {
+        TestShape {
+            saved_ray: Mutex::new(self.saved_ray()),
+        }
+    }
+}
+
+impl PartialEq for TestShape {
+    fn eq(&self, other: &Self) -> bool {
+        self.saved_ray() == other.saved_ray()
+    }
+}
@@ -29,6 +29,7 @@ pub trait Transformation {
         zy: f64,
     ) -> Self;
     fn view_transform(from: Point, to: Point, up: Vector) -> Self;
+    fn view_transform_with_roll(from: Point, to: Point, up: Vector, roll: f64) -> Self;
 
 }
 
@@ -162,6 +163,12 @@ impl Transformation for Matrix {
         ]);
         orientation * Matrix::translation(-from.0, -from.1, -from.2)
     }
+
+    // Turns the camera about its line of sight by roll radians, for tilted
+    // (dutch angle) shots, without hunting for an up vector that does it.
+    fn view_transform_with_roll(from: Point, to: Point, up: Vector, roll: f64) -> Matrix {
+        Matrix::rotation_z(roll) * Matrix::view_transform(from, to, up)
+    }
 }
 
 
@@ -372,6 +379,40 @@ mod tests {
         assert!((result.2 - expected.2).abs() < delta);
     }
 
+    #[test]
+    fn chained_transformations_with_a_shear_must_be_applied_in_reverse_order() {
+        let p = Tuple::point(1.0, 0.0, 1.0);
+        let a = Matrix::rotation_x(std::f64::consts::PI / 2.0);
+        let b = Matrix::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        let c = Matrix::translation(10.0, 5.0, 7.0);
+        let t = c * b * a;
+        let delta = 1e-10;
+        let expected = Tuple::point(10.0, 4.0, 7.0);
+        let result = t * p;
+        assert!((result.0 - expected.0).abs() < delta);
+        assert!((result.1 - expected.1).abs() < delta);
+        assert!((result.2 - expected.2).abs() < delta);
+    }
+
+    #[test]
+    fn fluent_shear_composes_in_the_order_it_is_written() {
+        let p = Tuple::point(1.0, 0.0, 1.0);
+        let t = Matrix::identity()
+            .rotate_x(std::f64::consts::PI / 2.0)
+            .shear(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            .translate(10.0, 5.0, 7.0);
+        let manual = Matrix::translation(10.0, 5.0, 7.0)
+            * Matrix::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            * Matrix::rotation_x(std::f64::consts::PI / 2.0);
+        assert_eq!(t, manual);
+
+        let delta = 1e-10;
+        let result = t * p;
+        assert!((result.0 - 10.0).abs() < delta);
+        assert!((result.1 - 4.0).abs() < delta);
+        assert!((result.2 - 7.0).abs() < delta);
+    }
+
     #[test]
     fn the_transformation_matrix_for_the_default_orientation() {
         let from = Tuple::point(0.0, 0.0, 0.0);
@@ -414,4 +455,23 @@ mod tests {
         assert_eq!(t, expected);
     }
 
-} 
\ No newline at end of file
+    #[test]
+    fn a_view_transform_with_no_roll_is_the_plain_view_transform() {
+        let from = Tuple::point(1.0, 3.0, 2.0);
+        let to = Tuple::point(4.0, -2.0, 8.0);
+        let up = Tuple::vector(1.0, 1.0, 0.0);
+        assert_eq!(Matrix::view_transform_with_roll(from, to, up, 0.0), Matrix::view_transform(from, to, up));
+    }
+
+    #[test]
+    fn rolling_the_view_keeps_the_line_of_sight() {
+        let from = Tuple::point(0.0, 0.0, -5.0);
+        let to = Tuple::point(0.0, 0.0, 0.0);
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+        let t = Matrix::view_transform_with_roll(from, to, up, std::f64::consts::PI / 2.0);
+        let ahead = &t * &Tuple::point(0.0, 0.0, 3.0);
+        assert!((ahead - Tuple::point(0.0, 0.0, -8.0)).magnitude() < 1e-9);
+        let above = &t * &Tuple::point(0.0, 1.0, -5.0);
+        assert!((above - Tuple::point(-1.0, 0.0, 0.0)).magnitude() < 1e-9);
+    }
+}
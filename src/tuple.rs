@@ -10,10 +10,17 @@ impl Tuple {
         self.3 == 1.0
     }
 
+    // Colors carry w = 0 just like vectors, so is_vector is also true for every
+    // color and the two can't be told apart by value. These predicates only
+    // rule points in or out; callers have to know which of the two they hold.
     pub fn is_vector(&self) -> bool {
         self.3 == 0.0
     }
 
+    pub fn is_color(&self) -> bool {
+        self.3 == 0.0
+    }
+
     pub fn point(x: f64, y: f64, z: f64) -> Self {
         Tuple::new(x, y, z, 1.0)
     }
@@ -27,10 +34,12 @@ impl Tuple {
     }
 
     pub fn magnitude(&self) -> f64 {
+        debug_assert!(!self.is_point(), "magnitude of a point");
         (self.0.powi(2) + self.1.powi(2) + self.2.powi(2) + self.3.powi(2)).sqrt()
     }
 
     pub fn normalize(&self) -> Self {
+        debug_assert!(!self.is_point(), "normalizing a point");
         let mag = self.magnitude();
         Tuple::new(self.0 / mag, self.1 / mag, self.2 / mag, self.3 / mag)
     }
@@ -40,6 +49,7 @@ impl Tuple {
     }
 
     pub fn cross(&self, other: Tuple) -> Tuple {
+        debug_assert!(!self.is_point() && !other.is_point(), "cross product of a point");
         Tuple::vector(
             self.1 * other.2 - self.2 * other.1,
             self.2 * other.0 - self.0 * other.2,
@@ -50,6 +60,33 @@ impl Tuple {
     pub fn reflect(&self, normal: Tuple) -> Tuple {
         *self - normal * 2.0 * self.dot(normal)
     }
+
+    // n_ratio is n1 / n2. None means total internal reflection.
+    pub fn refract(&self, normal: Tuple, n_ratio: f64) -> Option<Tuple> {
+        let cos_i = -self.dot(normal);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            return None;
+        }
+        let cos_t = (1.0 - sin2_t).sqrt();
+        Some(normal * (n_ratio * cos_i - cos_t) + *self * n_ratio)
+    }
+
+    pub fn as_array(&self) -> [f64; 4] {
+        [self.0, self.1, self.2, self.3]
+    }
+}
+
+impl From<[f64; 4]> for Tuple {
+    fn from(a: [f64; 4]) -> Self {
+        Tuple::new(a[0], a[1], a[2], a[3])
+    }
+}
+
+impl From<Tuple> for [f64; 4] {
+    fn from(t: Tuple) -> Self {
+        t.as_array()
+    }
 }
 
 pub type Point = Tuple;
@@ -133,6 +170,15 @@ impl std::ops::Div<f64> for Tuple {
 mod tests {
     use super::*;
 
+    #[test]
+    fn round_tripping_a_tuple_through_an_array() {
+        let t = Tuple::point(4.3, -4.2, 3.1);
+        let a: [f64; 4] = t.into();
+        assert_eq!(a, [4.3, -4.2, 3.1, 1.0]);
+        assert_eq!(t.as_array(), a);
+        assert_eq!(Tuple::from(a), t);
+    }
+
     #[test]
     fn tuple_with_w_1_is_a_point() {
         let a = Tuple::new(4.3, -4.2, 3.1, 1.0);
@@ -294,6 +340,30 @@ mod tests {
         assert_eq!(a.cross(b), Tuple::vector(-1.0, 2.0, -1.0));
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "cross product of a point")]
+    fn cross_product_of_two_points_is_caught_in_debug_builds() {
+        let a = Tuple::point(1.0, 2.0, 3.0);
+        let b = Tuple::point(2.0, 3.0, 4.0);
+        a.cross(b);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "normalizing a point")]
+    fn normalizing_a_point_is_caught_in_debug_builds() {
+        Tuple::point(1.0, 2.0, 3.0).normalize();
+    }
+
+    #[test]
+    fn colors_are_not_points() {
+        let c = Tuple::color(-0.5, 0.4, 1.7);
+        assert!(c.is_color());
+        assert!(!c.is_point());
+        assert!(!Tuple::point(1.0, 2.0, 3.0).is_color());
+    }
+
     #[test]
     fn colors_are_tuples() {
         let c = Tuple::color(-0.5, 0.4, 1.7);
@@ -350,4 +420,32 @@ mod tests {
         assert!((r.2 - 0.0).abs() < epsilon);
     }
 
+    #[test]
+    fn refracting_a_vector_straight_on() {
+        let v = Tuple::vector(0.0, -1.0, 0.0);
+        let n = Tuple::vector(0.0, 1.0, 0.0);
+        let r = v.refract(n, 1.0 / 1.5).unwrap();
+        assert_eq!(r, Tuple::vector(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn refracting_a_vector_at_45_degrees_follows_snells_law() {
+        let v = Tuple::vector(1.0, -1.0, 0.0).normalize();
+        let n = Tuple::vector(0.0, 1.0, 0.0);
+        let r = v.refract(n, 1.0 / 1.5).unwrap();
+        let sin_t = (45.0_f64.to_radians()).sin() / 1.5;
+        let epsilon = 1e-10;
+        assert!((r.magnitude() - 1.0).abs() < epsilon);
+        assert!((r.0 - sin_t).abs() < epsilon);
+        assert!((r.1 + (1.0 - sin_t.powi(2)).sqrt()).abs() < epsilon);
+        assert!((r.2 - 0.0).abs() < epsilon);
+    }
+
+    #[test]
+    fn refracting_past_the_critical_angle_is_total_internal_reflection() {
+        let v = Tuple::vector(1.0, -1.0, 0.0).normalize();
+        let n = Tuple::vector(0.0, 1.0, 0.0);
+        assert_eq!(v.refract(n, 1.5), None);
+    }
+
 }
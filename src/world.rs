@@ -1,8 +1,43 @@
-use crate::{color::Colors, intersection::{Intersection, Record}, intersections::Intersections, light::Light, material::Material, matrix::Matrix, object::Object, ray::Ray, transformation::Transformation, tuple::{Color, Tuple}};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{bounds::Bounds, color::Colors, intersection::{Intersection, Record, EPSILON}, intersections::Intersections, light::Light, material::Material, sequence::{CenteredSequence, Sequence}, matrix::Matrix, object::Object, ray::Ray, render_stats::RenderCounters, transformation::Transformation, tuple::{Color, Point, Tuple}};
+
+pub const DEFAULT_DEPTH: usize = 4;
+
+// What a ray sees when it misses everything.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sky {
+    None,
+    Gradient { horizon: Color, zenith: Color },
+}
+
+impl Sky {
+    // Rays at or below the horizon get the horizon colour; straight up gets
+    // the zenith.
+    pub fn color_at(&self, direction: Tuple) -> Color {
+        match *self {
+            Sky::None => Colors::black(),
+            Sky::Gradient { horizon, zenith } => {
+                let t = direction.normalize().1.clamp(0.0, 1.0);
+                horizon + (zenith - horizon) * t
+            }
+        }
+    }
+}
 
 pub struct World {
     objects: Vec<Object>,
     lights: Vec<Light>,
+    pub shadow_bias: f64,
+    pub transparent_shadows: bool,
+    pub sky: Sky,
+    // The fill light behind every material's ambient term, added once per
+    // hit. Left as None it follows the scene's lights; see fill_light.
+    pub ambient_light: Option<Color>,
+    // Scales every material's ambient term, for dimming or lifting the fill
+    // light of the whole scene at once.
+    pub ambient_scale: f64,
 }
 
 impl World {
@@ -10,6 +45,11 @@ impl World {
         World {
             objects: vec![],
             lights: vec![],
+            shadow_bias: EPSILON,
+            transparent_shadows: true,
+            sky: Sky::None,
+            ambient_light: None,
+            ambient_scale: 1.0,
         }
     }
 
@@ -31,6 +71,55 @@ impl World {
         self
     }
 
+    pub fn with_shadow_bias(mut self, shadow_bias: f64) -> World {
+        self.shadow_bias = shadow_bias;
+        self
+    }
+
+    pub fn with_transparent_shadows(mut self, transparent_shadows: bool) -> World {
+        self.transparent_shadows = transparent_shadows;
+        self
+    }
+
+    pub fn with_sky(mut self, sky: Sky) -> World {
+        self.sky = sky;
+        self
+    }
+
+    pub fn with_ambient_light(mut self, ambient_light: Color) -> World {
+        self.ambient_light = Some(ambient_light);
+        self
+    }
+
+    // The ambient_light override if there is one, otherwise the brightest
+    // light's intensity, so a single light tints ambient exactly as
+    // Material::lighting does and extra lights don't stack it. Lights sharing
+    // a position count as one light of their summed intensity, which keeps a
+    // cluster standing in for a soft light equal to the light it replaces.
+    // With no lights there is no ambient either.
+    pub fn fill_light(&self) -> Color {
+        if let Some(ambient_light) = self.ambient_light {
+            return ambient_light;
+        }
+        let mut sources: Vec<(Point, Color)> = vec![];
+        for light in &self.lights {
+            match sources.iter_mut().find(|(position, _)| *position == light.position()) {
+                Some((_, intensity)) => *intensity = *intensity + light.intensity(),
+                None => sources.push((light.position(), light.intensity())),
+            }
+        }
+        sources
+            .into_iter()
+            .map(|(_, intensity)| intensity)
+            .max_by(|a, b| (a.0 + a.1 + a.2).total_cmp(&(b.0 + b.1 + b.2)))
+            .unwrap_or(Colors::black())
+    }
+
+    pub fn with_ambient_scale(mut self, ambient_scale: f64) -> World {
+        self.ambient_scale = ambient_scale;
+        self
+    }
+
     pub fn add_lights(&mut self, lights: Vec<Light>) {
         self.lights.extend(lights);
     }
@@ -39,6 +128,90 @@ impl World {
         self.lights.push(light);
     }
 
+    pub fn add_visible_light(&mut self, light: Light, radius: f64) {
+        let p = light.position();
+        let proxy = Object::sphere()
+            .with_transform(Matrix::translation(p.0, p.1, p.2) * Matrix::scaling(radius, radius, radius))
+            .with_material(Material::new()
+                .with_color(Colors::black())
+                .with_ambient(0.0)
+                .with_diffuse(0.0)
+                .with_specular(0.0)
+                .with_emissive(light.intensity()))
+            .with_casts_shadow(false);
+        self.objects.push(proxy);
+        self.lights.push(light);
+    }
+
+    pub fn get_objects(&self) -> &Vec<Object> {
+        &self.objects
+    }
+
+    // Looks through top-level objects and everything nested in groups.
+    pub fn find(&self, id: usize) -> Option<&Object> {
+        self.objects.iter().find_map(|object| object.find(id))
+    }
+
+    pub fn get_lights(&self) -> &Vec<Light> {
+        &self.lights
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let mut bounds = Bounds::empty();
+        for object in &self.objects {
+            bounds.merge(&object.bounds());
+        }
+        bounds
+    }
+
+    // Center and radius of a sphere around every bounded object. Unbounded
+    // objects such as floor planes are left out so they don't swamp the scene.
+    pub fn bounding_sphere(&self) -> (Point, f64) {
+        let mut bounds = Bounds::empty();
+        for object in &self.objects {
+            let object_bounds = object.bounds();
+            if object_bounds.is_finite() {
+                bounds.merge(&object_bounds);
+            }
+        }
+
+        if bounds.is_empty() {
+            return (Tuple::point(0.0, 0.0, 0.0), 0.0);
+        }
+        let center = bounds.center();
+        (center, (bounds.max - center).magnitude())
+    }
+
+    // A copy of the world holding only the objects on one layer, with the
+    // same lights and settings. Everything else is gone entirely, so it
+    // neither shows up nor casts shadows. Layers inside groups count too;
+    // see Object::on_layer.
+    pub fn on_layer(&self, layer: u32) -> World {
+        World {
+            objects: self.objects.iter().filter_map(|o| o.on_layer(layer)).collect(),
+            lights: self.lights.clone(),
+            shadow_bias: self.shadow_bias,
+            transparent_shadows: self.transparent_shadows,
+            sky: self.sky,
+            ambient_light: self.ambient_light,
+            ambient_scale: self.ambient_scale,
+        }
+    }
+
+    // Catches setups that would render wrongly or not at all: no lights,
+    // singular transforms and NaN material parameters, anywhere in the tree.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+        if self.lights.is_empty() {
+            errors.push("world has no lights".to_string());
+        }
+        for object in &self.objects {
+            validate_object(object, &mut errors);
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
     pub fn default_world() -> World {
         let light = Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
         let s1 = Object::sphere()
@@ -51,91 +224,245 @@ impl World {
         World {
             objects: vec![s1, s2],
             lights: vec![light],
+            shadow_bias: EPSILON,
+            transparent_shadows: true,
+            sky: Sky::None,
+            ambient_light: None,
+            ambient_scale: 1.0,
         }
     }
 
     pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        self.intersect_counted(ray, None)
+    }
+
+    fn intersect_counted(&self, ray: &Ray, stats: Option<&RenderCounters>) -> Vec<Intersection<'_>> {
+        if let Some(stats) = stats {
+            stats.add_intersection_tests(self.objects.len());
+        }
         let mut xs: Vec<Intersection> = vec![];
         for object in &self.objects {
-            let mut object_xs = object.intersect(ray);
-            xs.append(&mut object_xs);
+            object.intersect_into(ray, &mut xs);
         }
-        xs.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+        xs.sort_by_t();
         xs
     }
 
+    // As intersect, but the object with the given id never shows up. A group's
+    // id drops everything inside it; a child's id drops just that child.
+    pub fn intersect_excluding(&self, ray: &Ray, exclude_id: usize) -> Vec<Intersection<'_>> {
+        let mut xs: Vec<Intersection> = vec![];
+        for object in self.objects.iter().filter(|o| o.id != exclude_id) {
+            xs.extend(object.intersect(ray).into_iter().filter(|i| i.object.id != exclude_id));
+        }
+        xs.sort_by_t();
+        xs
+    }
+
+    pub fn for_each_intersection<'a>(&'a self, ray: &Ray, f: impl FnMut(Intersection<'a>)) {
+        self.for_each_intersection_counted(ray, None, f)
+    }
+
+    fn for_each_intersection_counted<'a>(&'a self, ray: &Ray, stats: Option<&RenderCounters>, mut f: impl FnMut(Intersection<'a>)) {
+        if let Some(stats) = stats {
+            stats.add_intersection_tests(self.objects.len());
+        }
+        for object in &self.objects {
+            for i in object.intersect(ray) {
+                f(i);
+            }
+        }
+    }
+
+    // Same as intersect().hit(), but one pass with no merged list or sort.
+    pub fn hit(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        self.hit_counted(ray, None)
+    }
+
+    fn hit_counted(&self, ray: &Ray, stats: Option<&RenderCounters>) -> Option<Intersection<'_>> {
+        let mut nearest: Option<Intersection> = None;
+        self.for_each_intersection_counted(ray, stats, |i| {
+            if i.t >= 0.0 && nearest.as_ref().is_none_or(|n| i.t < n.t) {
+                nearest = Some(i);
+            }
+        });
+        nearest
+    }
+
+    // Nearest hit for each ray, in the same order, cast in parallel when the
+    // parallel feature is on.
+    pub fn cast_rays(&self, rays: &[Ray]) -> Vec<Option<Intersection<'_>>> {
+        #[cfg(feature = "parallel")]
+        let rays = rays.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let rays = rays.iter();
+        rays.map(|ray| self.hit(ray)).collect()
+    }
+
     pub fn shade_hit(&self, record: &Record, depth: usize) -> Tuple {
-        let mut surface: Color = Colors::black();
+        self.shade_hit_with(record, depth, &mut CenteredSequence, None)
+    }
+
+    pub fn shade_hit_with(&self, record: &Record, depth: usize, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> Tuple {
+        let depth = match record.object.get_material().max_bounces {
+            Some(max_bounces) => depth.min(max_bounces),
+            None => depth,
+        };
+
+        // Ambient goes in once, from the world's fill light; each light then
+        // adds only its own diffuse and specular.
+        let mut surface = record.object.get_material().ambient_at(&record.object, record.over_point, self.fill_light()) * self.ambient_scale;
         for light in &self.lights {
-            surface = surface + record.object.get_material().lighting(
+            let (_, diffuse, specular) = record.object.get_material().lighting_components(
                 &record.object,
                 light,
                 record.over_point,
                 record.eyev,
                 record.normalv,
-                self.is_shadowed(&record.over_point),
+                1.0,
             );
+            surface = surface + (diffuse + specular) * self.intensity_at(light, &record.over_point, seq, stats);
         }
 
-        let reflected = self.reflected_color(record, depth);
-        let refracted = self.refracted_color(record, depth);
-
         let material = record.object.get_material();
+        surface = surface + material.emissive;
 
-        if material.reflectivity > 0. && material.transparency > 0. {
-            let reflectance = record.schlick;
-            return surface 
-                + reflected * reflectance 
-                + refracted * (1. - reflectance);
-        }
+        // Only recurse for the parts of the material that actually bounce
+        // light; a plain diffuse surface casts no secondary rays at all.
+        let color = match (material.reflectivity > 0., material.transparency > 0.) {
+            (true, true) => {
+                let reflectance = record.schlick;
+                surface
+                    + self.reflected_color_with(record, depth, seq, stats) * reflectance
+                    + self.refracted_color_with(record, depth, seq, stats) * (1. - reflectance)
+            }
+            (true, false) => surface + self.reflected_color_with(record, depth, seq, stats),
+            (false, true) => surface + self.refracted_color_with(record, depth, seq, stats),
+            (false, false) => surface,
+        };
 
-        surface 
-        + self.reflected_color(record, depth) 
-        + self.refracted_color(record, depth)
+        if material.opacity < 1. && depth > 0 {
+            // See straight through the surface, without bending the ray.
+            let through = Ray::new(record.under_point, -record.eyev);
+            let behind = self.color_at_with(&through, depth - 1, seq, stats);
+            return color * material.opacity + behind * (1. - material.opacity);
+        }
 
+        color
     }
 
     pub fn color_at(&self, ray: &Ray, depth: usize) -> Tuple {
-        let xs = self.intersect(ray);
-        match xs.hit() {
-            Some(hit) => {
-                let record = hit.prepare_computations(ray, &vec![]);
-                self.shade_hit(&record, depth)
-            }
-            None => Colors::black(),
-        }
+        self.color_at_with(ray, depth, &mut CenteredSequence, None)
+    }
+
+    pub fn color_at_with(&self, ray: &Ray, depth: usize, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> Tuple {
+        self.shade_first_hit(ray, depth, seq, stats).unwrap_or_else(|| self.sky.color_at(ray.direction))
+    }
+
+    // Unlike color_at, a ray that misses everything gives None rather than
+    // black, so misses can be composited over a background.
+    pub fn hit_color_at(&self, ray: &Ray) -> Option<Color> {
+        self.hit_color_at_with(ray, &mut CenteredSequence, None)
+    }
+
+    pub fn hit_color_at_with(&self, ray: &Ray, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> Option<Color> {
+        self.shade_first_hit(ray, DEFAULT_DEPTH, seq, stats)
+    }
+
+    fn shade_first_hit(&self, ray: &Ray, depth: usize, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> Option<Color> {
+        self.hit_counted(ray, stats).map(|hit| {
+            // Refractive indices need every intersection along the ray, but
+            // only a transparent hit uses them.
+            let xs = if hit.object.get_material().transparency > 0. { self.intersect_counted(ray, stats) } else { vec![] };
+            let record = hit.prepare_computations_with_bias(ray, &xs, self.shadow_bias);
+            self.shade_hit_with(&record, depth, seq, stats)
+        })
     }
 
     pub fn is_shadowed(&self, point: &Tuple) -> bool {
-        let v = self.lights[0].position() - *point;
+        self.visibility(self.lights[0].position(), point, None) == 0.0
+    }
+
+    pub fn visibility_at(&self, light: &Light, point: &Tuple) -> f64 {
+        self.visibility_at_with(light, point, &mut CenteredSequence, None)
+    }
+
+    // Fraction of the light's shadow samples that can see the point.
+    pub fn visibility_at_with(&self, light: &Light, point: &Tuple, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> f64 {
+        let samples = light.sample_points_with(seq);
+        let visible: f64 = samples
+            .iter()
+            .map(|target| self.visibility(*target, point, stats))
+            .sum();
+        visible / samples.len() as f64
+    }
+
+    // Everything that scales a light's diffuse and specular at a point, per
+    // channel: sampled visibility (soft or hard) and distance falloff. A plain
+    // point light gives white when visible and black in shadow.
+    pub fn intensity_at(&self, light: &Light, point: &Tuple, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> Color {
+        let distance = (light.position() - *point).magnitude();
+        Color::white() * (self.visibility_at_with(light, point, seq, stats) * light.falloff_at(distance))
+    }
+
+    // How much light passes from target to point. Objects that don't cast
+    // shadows are ignored, hits closer than the bias are the surface the point
+    // sits on, and with transparent_shadows each blocker lets through its
+    // transparency once; otherwise every blocker is opaque.
+    fn visibility(&self, target: Tuple, point: &Tuple, stats: Option<&RenderCounters>) -> f64 {
+        let v = target - *point;
         let distance = v.magnitude();
         let direction = v.normalize();
         let r = Ray::new(*point, direction);
-        let xs = self.intersect(&r);
-        if let Some(hit) = xs.hit() {
-            if hit.t < distance {
-                return true;
-            }
+        if let Some(stats) = stats {
+            stats.add_shadow_ray();
         }
-        false
+        let mut blockers: Vec<(usize, f64)> = vec![];
+        self.for_each_intersection_counted(&r, stats, |i| {
+            if i.object.casts_shadow
+                && i.t >= self.shadow_bias
+                && i.t < distance
+                && !blockers.iter().any(|(id, _)| *id == i.object.id)
+            {
+                let transparency = if self.transparent_shadows {
+                    i.object.get_material().transparency
+                } else {
+                    0.0
+                };
+                blockers.push((i.object.id, transparency));
+            }
+        });
+        blockers.iter().map(|(_, transparency)| transparency).product()
     }
 
     pub fn reflected_color(&self, record: &Record, depth: usize) -> Color {
+        self.reflected_color_with(record, depth, &mut CenteredSequence, None)
+    }
+
+    pub fn reflected_color_with(&self, record: &Record, depth: usize, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> Color {
         if depth <= 0 {
             return Colors::black();
         }
 
-        if record.object.get_material().reflectivity == 0. {
+        let material = record.object.get_material();
+        if material.reflectivity == 0. {
             return Colors::black();
         }
 
         let reflect_ray = Ray::new(record.over_point, record.reflectv);
-        let color = self.color_at(&reflect_ray, depth - 1);
+        if let Some(stats) = stats {
+            stats.add_reflection_ray();
+        }
+        let color = self.color_at_with(&reflect_ray, depth - 1, seq, stats);
 
-        color * record.object.get_material().reflectivity
+        color * material.reflectivity_at(record.schlick)
     }
 
     pub fn refracted_color(&self, record: &Record, depth: usize) -> Color {
+        self.refracted_color_with(record, depth, &mut CenteredSequence, None)
+    }
+
+    pub fn refracted_color_with(&self, record: &Record, depth: usize, seq: &mut dyn Sequence, stats: Option<&RenderCounters>) -> Color {
         if depth <= 0 {
             return Colors::black();
         }
@@ -144,25 +471,50 @@ impl World {
             return Colors::black();
         }
 
-        let n_ratio = record.n1 / record.n2;
-        let cos_i = record.eyev.dot(record.normalv);
-        let sin2_t = n_ratio.powi(2) * (1. - cos_i.powi(2));
-
-        if sin2_t > 1. {
+        let n_ratio = if record.object.get_material().thin { 1.0 } else { record.n1 / record.n2 };
+        let Some(direction) = (-record.eyev).refract(record.normalv, n_ratio) else {
             return Colors::black();
+        };
+        let refract_ray = Ray::new(record.under_point, direction);
+        if let Some(stats) = stats {
+            stats.add_refraction_ray();
         }
 
-        let cos_t = (1. - sin2_t).sqrt();
-        let direction = record.normalv * (n_ratio * cos_i - cos_t) - record.eyev * n_ratio;
-        let refract_ray = Ray::new(record.under_point, direction);
- 
-        self.color_at(&refract_ray, depth - 1) * record.object.get_material().transparency
+        self.color_at_with(&refract_ray, depth - 1, seq, stats) * record.object.get_material().transparency
+    }
+}
+
+fn validate_object(object: &Object, errors: &mut Vec<String>) {
+    if !object.get_transform().is_invertible() {
+        errors.push(format!("object {} has a singular transform", object.id));
+    }
+
+    let material = object.get_material();
+    let parameters = [
+        ("color", material.color.0 + material.color.1 + material.color.2),
+        ("ambient", material.ambient),
+        ("diffuse", material.diffuse),
+        ("specular", material.specular),
+        ("shininess", material.shininess),
+        ("reflectivity", material.reflectivity),
+        ("transparency", material.transparency),
+        ("opacity", material.opacity),
+        ("refractive_index", material.refractive_index),
+    ];
+    for (name, value) in parameters {
+        if value.is_nan() {
+            errors.push(format!("object {} has a NaN {}", object.id, name));
+        }
+    }
+
+    for child in object.get_children() {
+        validate_object(child, errors);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{matrix::Matrix, pattern::Pattern, ray::Ray, transformation::Transformation, tuple::Tuple};
+    use crate::{camera::Camera, matrix::Matrix, pattern::Pattern, ray::Ray, transformation::Transformation, tuple::Tuple};
 
     use super::*;
 
@@ -244,6 +596,26 @@ mod tests {
         assert!((c.2 - 0.2855).abs() < delta);
     }
 
+    #[test]
+    fn a_ray_into_empty_space_has_no_hit_color() {
+        let world = World::default_world();
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(world.hit_color_at(&r), None);
+    }
+
+    #[test]
+    fn a_ray_hitting_a_black_surface_has_a_black_hit_color() {
+        let black = Object::sphere().with_material(Material::new()
+            .with_color(Colors::black())
+            .with_ambient(0.0)
+            .with_specular(0.0));
+        let world = World::new()
+            .with_objects(vec![black])
+            .with_lights(vec![Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0))]);
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(world.hit_color_at(&r), Some(Colors::black()));
+    }
+
     #[test]
     fn the_color_with_an_intersection_behind_the_ray() {
         let mut world = World::default_world();
@@ -254,6 +626,173 @@ mod tests {
         assert_eq!(c, world.objects[1].get_material().color);
     }
 
+    #[test]
+    fn streaming_intersections_yields_the_same_set_as_intersect() {
+        let world = World::default_world();
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let mut ts = vec![];
+        world.for_each_intersection(&r, |i| ts.push(i.t));
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let expected: Vec<f64> = world.intersect(&r).iter().map(|i| i.t).collect();
+        assert_eq!(ts, expected);
+    }
+
+    #[test]
+    fn adding_a_visible_light_adds_an_emissive_proxy_sphere() {
+        let mut world = World::default_world();
+        let light = Light::new(Tuple::point(0.0, 5.0, -5.0), Tuple::color(1.0, 0.8, 0.6));
+        world.add_visible_light(light, 0.25);
+
+        assert_eq!(world.objects.len(), 3);
+        assert_eq!(world.lights.len(), 2);
+        let proxy = &world.objects[2];
+        assert_eq!(proxy.get_material().emissive, light.intensity());
+        assert!(!proxy.casts_shadow);
+        assert_eq!(proxy.get_transform() * &Tuple::point(0.0, 0.0, 0.0), light.position());
+    }
+
+    #[test]
+    fn a_visible_light_does_not_shadow_its_surroundings() {
+        let mut world = World::new().with_objects(vec![Object::plane()]);
+        world.add_visible_light(Light::new(Tuple::point(0.0, 5.0, 0.0), Tuple::color(1.0, 1.0, 1.0)), 0.5);
+        assert!(!world.is_shadowed(&Tuple::point(0.0, 0.001, 0.0)));
+
+        let r = Ray::new(Tuple::point(0.0, 5.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let c = world.color_at(&r, 0);
+        assert_eq!(c, Tuple::color(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn the_default_shadow_bias() {
+        assert_eq!(World::new().shadow_bias, 0.0001);
+        assert_eq!(World::default_world().shadow_bias, 0.0001);
+    }
+
+    #[test]
+    fn increasing_the_shadow_bias_removes_acne_from_an_imprecise_hit() {
+        // A hit reported slightly past the surface, as a less precise
+        // intersection routine would produce, lit at a grazing angle.
+        let s = Object::sphere();
+        let light = Light::new(Tuple::point(0.0, 10.0, -1.2), Tuple::color(1.0, 1.0, 1.0));
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = vec![Intersection::new(4.001, &s)];
+
+        let world = World::new().with_objects(vec![s.clone()]).with_lights(vec![light]);
+        let comps = xs[0].prepare_computations_with_bias(&r, &xs, world.shadow_bias);
+        assert!(world.is_shadowed(&comps.over_point));
+
+        let world = world.with_shadow_bias(0.01);
+        let comps = xs[0].prepare_computations_with_bias(&r, &xs, world.shadow_bias);
+        assert!(!world.is_shadowed(&comps.over_point));
+    }
+
+    #[test]
+    fn a_hard_light_visibility_matches_is_shadowed() {
+        let world = World::default_world();
+        let light = world.lights[0];
+        for p in [
+            Tuple::point(0.0, 10.0, 0.0),
+            Tuple::point(10.0, -10.0, 10.0),
+            Tuple::point(-20.0, 20.0, -20.0),
+            Tuple::point(-2.0, 2.0, -2.0),
+        ] {
+            let expected = if world.is_shadowed(&p) { 0.0 } else { 1.0 };
+            assert_eq!(world.visibility_at(&light, &p), expected);
+        }
+    }
+
+    #[test]
+    fn a_point_light_intensity_is_white_when_lit_and_black_in_shadow() {
+        let world = World::default_world();
+        let light = world.lights[0];
+        let lit = Tuple::point(-2.0, 2.0, -2.0);
+        let shadowed = Tuple::point(10.0, -10.0, 10.0);
+        assert_eq!(world.intensity_at(&light, &lit, &mut CenteredSequence, None), Tuple::color(1.0, 1.0, 1.0));
+        assert_eq!(world.intensity_at(&light, &shadowed, &mut CenteredSequence, None), Tuple::color(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn light_falloff_scales_intensity_by_distance() {
+        let light = Light::new(Tuple::point(0.0, 10.0, 0.0), Tuple::color(1.0, 1.0, 1.0)).with_falloff(1.0, 0.0, 0.01);
+        let world = World::new().with_lights(vec![light]);
+        let i = world.intensity_at(&light, &Tuple::point(0.0, 0.0, 0.0), &mut CenteredSequence, None);
+        assert_eq!(i, Tuple::color(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn a_soft_light_gives_fractional_visibility_in_the_penumbra() {
+        let light = Light::new(Tuple::point(0.0, 10.0, 0.0), Tuple::color(1.0, 1.0, 1.0)).with_softness(2.0);
+        let world = World::new()
+            .with_objects(vec![Object::sphere().with_transform(Matrix::translation(0.0, 5.0, 0.0))])
+            .with_lights(vec![light]);
+
+        let penumbra = world.visibility_at(&light, &Tuple::point(2.0, 0.0, 0.0));
+        assert!(penumbra > 0.0 && penumbra < 1.0);
+        assert_eq!(world.visibility_at(&light, &Tuple::point(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(world.visibility_at(&light, &Tuple::point(10.0, 0.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn each_light_is_shadow_tested_on_its_own() {
+        let world = World::new()
+            .with_objects(vec![Object::sphere().with_transform(Matrix::translation(0.0, 0.0, -5.0))])
+            .with_lights(vec![
+                Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0)),
+                Light::new(Tuple::point(0.0, 0.0, 10.0), Tuple::color(1.0, 1.0, 1.0)),
+            ]);
+        let p = Tuple::point(0.0, 0.0, 0.0);
+        assert_eq!(world.visibility_at(&world.lights[0], &p), 0.0);
+        assert_eq!(world.visibility_at(&world.lights[1], &p), 1.0);
+    }
+
+    #[test]
+    fn a_point_on_a_lit_surface_does_not_shadow_itself() {
+        let world = World::new()
+            .with_objects(vec![Object::sphere()])
+            .with_lights(vec![Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0))]);
+        let p = Tuple::point(0.0, 0.0, -1.0);
+        assert!(!world.is_shadowed(&p));
+        assert_eq!(world.visibility_at(&world.lights[0], &p), 1.0);
+    }
+
+    #[test]
+    fn a_point_inside_a_bowl_is_lit_from_above() {
+        // A ring of cubes around a floor cube, open to the light overhead.
+        let mut objects = vec![Object::cube().with_transform(Matrix::scaling(3.0, 0.5, 3.0))];
+        for (x, z) in [(3.5, 0.0), (-3.5, 0.0), (0.0, 3.5), (0.0, -3.5)] {
+            objects.push(Object::cube().with_transform(Matrix::translation(x, 1.5, z) * Matrix::scaling(0.5, 1.0, 3.0)));
+        }
+        let world = World::new()
+            .with_objects(objects)
+            .with_lights(vec![Light::new(Tuple::point(0.0, 10.0, 0.0), Tuple::color(1.0, 1.0, 1.0))]);
+        let r = Ray::new(Tuple::point(1.0, 5.0, 1.0), Tuple::vector(0.0, -1.0, 0.0));
+        let xs = world.intersect(&r);
+        let comps = xs.hit().unwrap().prepare_computations(&r, &xs);
+        assert!(!world.is_shadowed(&comps.point));
+        assert!(!world.is_shadowed(&comps.over_point));
+    }
+
+    #[test]
+    fn transparent_objects_attenuate_rather_than_block_shadows() {
+        let light = Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let p = Tuple::point(0.0, 0.0, 5.0);
+        let blocker = Object::sphere().with_material(Material::new().with_transparency(0.6));
+
+        let world = World::new().with_objects(vec![blocker.clone()]).with_lights(vec![light]);
+        assert!((world.visibility_at(&light, &p) - 0.6).abs() < 1e-9);
+        assert!(!world.is_shadowed(&p));
+
+        let world = world.with_transparent_shadows(false);
+        assert_eq!(world.visibility_at(&light, &p), 0.0);
+
+        let world = World::new().with_objects(vec![blocker.with_casts_shadow(false)]).with_lights(vec![light]);
+        assert_eq!(world.visibility_at(&light, &p), 1.0);
+
+        let world = World::new().with_objects(vec![Object::sphere()]).with_lights(vec![light]);
+        assert_eq!(world.visibility_at(&light, &p), 0.0);
+        assert!(world.is_shadowed(&p));
+    }
+
     #[test]
     fn there_is_no_shadow_when_nothing_is_collinear_with_point_and_light() {
         let world = World::default_world();
@@ -327,6 +866,34 @@ mod tests {
         assert!((color.2 - 0.14274).abs() < delta);
     }
 
+    #[test]
+    fn a_mirror_reflects_the_sky() {
+        let tint = Tuple::color(0.2, 0.4, 0.8);
+        let mirror = Object::plane()
+            .with_material(Material::new().with_reflectivity(1.0))
+            .with_transform(Matrix::translation(0.0, -1.0, 0.0));
+        let world = World::new()
+            .with_objects(vec![mirror])
+            .with_lights(vec![Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0))])
+            .with_sky(Sky::Gradient { horizon: tint, zenith: tint });
+        let r = Ray::new(Tuple::point(0.0, 0.0, -3.0), Tuple::vector(0.0, -2_f64.sqrt() / 2.0, 2_f64.sqrt() / 2.0));
+        let i = Intersection::new(2_f64.sqrt(), &world.objects[0]);
+        let comps = i.prepare_computations(&r, &vec![]);
+        let color = world.reflected_color(&comps, 4);
+        assert!((color.0 - tint.0).abs() < 1e-9);
+        assert!((color.1 - tint.1).abs() < 1e-9);
+        assert!((color.2 - tint.2).abs() < 1e-9);
+
+        let horizon = Tuple::color(1.0, 1.0, 1.0);
+        let world = world.with_sky(Sky::Gradient { horizon, zenith: tint });
+        let i = Intersection::new(2_f64.sqrt(), &world.objects[0]);
+        let comps = i.prepare_computations(&r, &vec![]);
+        let expected = horizon + (tint - horizon) * (2_f64.sqrt() / 2.0);
+        let color = world.reflected_color(&comps, 4);
+        assert!((color.0 - expected.0).abs() < 1e-9);
+        assert!((color.2 - expected.2).abs() < 1e-9);
+    }
+
     #[test]
     fn shade_hit_with_a_reflective_material() {
         let mut world = World::default_world();
@@ -437,6 +1004,8 @@ mod tests {
     #[test]
     fn shade_hit_with_a_transparent_material() {
         let mut world = World::default_world();
+        // The book's values treat transparent objects as opaque shadow casters.
+        world.transparent_shadows = false;
         let floor = Object::plane()
             .with_transform(Matrix::translation(0.0, -1.0, 0.0))
             .with_material(Material::new().with_transparency(0.5).with_refractive_index(1.5));
@@ -459,6 +1028,8 @@ mod tests {
     #[test]
     fn shade_hit_with_a_reflective_transparent_material() {
         let mut world = World::default_world();
+        // The book's values treat transparent objects as opaque shadow casters.
+        world.transparent_shadows = false;
         let floor = Object::plane()
             .with_transform(Matrix::translation(0.0, -1.0, 0.0))
             .with_material(Material::new()
@@ -482,4 +1053,435 @@ mod tests {
         assert!((c.1 - 0.69643).abs() < delta);
         assert!((c.2 - 0.69243).abs() < delta);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn the_bounding_sphere_skips_unbounded_objects() {
+        let w = World::new().with_objects(vec![
+            Object::plane(),
+            Object::sphere().with_transform(Matrix::identity().translate(2.0, 0.0, 0.0)),
+            Object::sphere().with_transform(Matrix::identity().translate(-2.0, 0.0, 0.0)),
+        ]);
+        let (center, radius) = w.bounding_sphere();
+        assert_eq!(center, Tuple::point(0.0, 0.0, 0.0));
+        assert!((radius - 11.0_f64.sqrt()).abs() < 1e-10);
+        assert!(!w.bounds().is_finite());
+    }
+
+    #[test]
+    fn the_bounding_sphere_of_an_empty_world() {
+        assert_eq!(World::new().bounding_sphere(), (Tuple::point(0.0, 0.0, 0.0), 0.0));
+    }
+
+    #[test]
+    fn the_nearest_hit_matches_the_hit_of_all_intersections() {
+        let mut scenes = vec![World::default_world()];
+
+        let mut w = World::new();
+        w.add_object(Object::plane());
+        w.add_object(Object::sphere().with_transform(Matrix::translation(0.0, 1.0, 0.0)));
+        w.add_object(Object::cube().with_transform(Matrix::translation(2.0, 1.0, 3.0)));
+        scenes.push(w);
+
+        // Two coincident spheres, so the tie goes to the first one added.
+        scenes.push(World::new().with_objects(vec![Object::sphere(), Object::sphere(), Object::cylinder()]));
+
+        let rays = [
+            Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0)),
+            Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0)),
+            Ray::new(Tuple::point(0.0, 3.0, -5.0), Tuple::vector(0.0, -0.5, 1.0).normalize()),
+            Ray::new(Tuple::point(5.0, 5.0, 5.0), Tuple::vector(0.0, 1.0, 0.0)),
+        ];
+        for w in &scenes {
+            for r in &rays {
+                let expected = w.intersect(r).hit().map(|i| (i.t, i.object.id));
+                assert_eq!(w.hit(r).map(|i| (i.t, i.object.id)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn fresnel_reflections_strengthen_at_grazing_incidence() {
+        let floor = Object::plane()
+            .with_material(Material::new().with_reflectivity(0.2).with_fresnel_reflectivity(true));
+        let w = World::new().with_objects(vec![floor.clone()]);
+        let head_on = Ray::new(Tuple::point(0.0, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0));
+        let i = Intersection::new(1.0, &floor);
+        let comps = i.prepare_computations(&head_on, &vec![i.clone()]);
+        assert!((floor.get_material().reflectivity_at(comps.schlick) - 0.2).abs() < 1e-10);
+
+        let grazing = Ray::new(Tuple::point(0.0, 0.001, -10.0), Tuple::vector(0.0, -0.0001, 1.0).normalize());
+        let xs = w.intersect(&grazing);
+        let comps = xs[0].prepare_computations(&grazing, &xs);
+        assert!(floor.get_material().reflectivity_at(comps.schlick) > 0.99);
+    }
+
+    #[test]
+    fn casting_a_batch_of_rays_matches_single_hits() {
+        let w = World::default_world();
+        let c = Camera::new(11, 11, std::f64::consts::PI / 2.0)
+            .with_transform(Matrix::view_transform(
+                Tuple::point(0.0, 0.0, -5.0),
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::vector(0.0, 1.0, 0.0),
+            ));
+        let rays: Vec<Ray> = [(0, 0), (10, 0), (0, 10), (10, 10), (5, 5)]
+            .iter()
+            .map(|&(x, y)| c.ray_for_pixel(x, y))
+            .collect();
+        let hits = w.cast_rays(&rays);
+        assert_eq!(hits.len(), rays.len());
+        for (ray, hit) in rays.iter().zip(hits.iter()) {
+            let expected = w.hit(ray).map(|i| (i.t, i.object.id));
+            assert_eq!(hit.as_ref().map(|i| (i.t, i.object.id)), expected);
+        }
+        assert!(hits[4].is_some());
+    }
+
+    #[test]
+    fn a_half_opaque_sphere_blends_with_what_is_behind_it() {
+        let sphere = Object::sphere()
+            .with_material(Material::unlit(Tuple::color(1.0, 0.0, 0.0)).with_opacity(0.5));
+        let wall = Object::plane()
+            .with_transform(Matrix::rotation_x(std::f64::consts::PI / 2.0).translate(0.0, 0.0, 5.0))
+            .with_material(Material::unlit(Tuple::color(0.0, 0.0, 1.0)));
+        let light = Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let w = World::new().with_objects(vec![sphere, wall]).with_lights(vec![light]);
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+
+        // Front face over (back face over the wall).
+        let c = w.color_at(&r, 5);
+        assert!((c.0 - 0.75).abs() < 1e-10);
+        assert!((c.1 - 0.0).abs() < 1e-10);
+        assert!((c.2 - 0.25).abs() < 1e-10);
+
+        let refractive = Object::sphere()
+            .with_material(Material::unlit(Tuple::color(1.0, 0.0, 0.0)).with_transparency(0.5));
+        let wall = w.get_objects()[1].clone();
+        let w = World::new().with_objects(vec![refractive, wall]).with_lights(w.get_lights().clone());
+        assert_ne!(w.color_at(&r, 5), c);
+    }
+
+    #[test]
+    fn finding_a_nested_child_by_id() {
+        let s = Object::sphere().with_transform(Matrix::translation(1.0, 2.0, 3.0));
+        let mut inner = Object::group();
+        inner.add_child(s.clone());
+        let mut outer = Object::group();
+        outer.add_child(inner);
+        let w = World::new().with_objects(vec![Object::plane(), outer]);
+
+        let found = w.find(s.id).unwrap();
+        assert_eq!(found.id, s.id);
+        assert_eq!(found.get_transform(), s.get_transform());
+        assert!(w.find(Object::sphere().id).is_none());
+    }
+
+    #[test]
+    fn a_lit_world_with_invertible_transforms_is_valid() {
+        assert_eq!(World::default_world().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validation_reports_a_zero_scale_transform() {
+        let mut w = World::default_world();
+        let flat = Object::sphere().with_transform(Matrix::scaling(1.0, 0.0, 1.0));
+        let id = flat.id;
+        let mut g = Object::group();
+        g.add_child(flat);
+        w.add_object(g);
+
+        assert_eq!(w.validate(), Err(vec![format!("object {} has a singular transform", id)]));
+
+        // The degenerate object only produces NaN hits, which never win.
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(w.hit(&r).map(|i| i.t), Some(4.0));
+        assert_eq!(w.intersect(&r).iter().filter(|i| !i.t.is_nan()).count(), 4);
+    }
+
+    #[test]
+    fn validation_reports_missing_lights_and_nan_materials() {
+        let s = Object::sphere().with_material(Material::new().with_diffuse(f64::NAN));
+        let id = s.id;
+        let w = World::new().with_objects(vec![s]);
+        assert_eq!(w.validate(), Err(vec![
+            "world has no lights".to_string(),
+            format!("object {} has a NaN diffuse", id),
+        ]));
+    }
+
+    #[test]
+    fn a_cluster_of_dim_lights_shades_like_one_bright_light() {
+        let single = World::default_world();
+        let mut cluster = World::default_world().with_lights(vec![]);
+        for _ in 0..4 {
+            cluster.add_light(Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(0.25, 0.25, 0.25)));
+        }
+
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let expected = single.color_at(&r, 4);
+        let c = cluster.color_at(&r, 4);
+        let epsilon = 1e-10;
+        assert!((c.0 - expected.0).abs() < epsilon);
+        assert!((c.1 - expected.1).abs() < epsilon);
+        assert!((c.2 - expected.2).abs() < epsilon);
+    }
+
+    // A point on the far sphere, hidden from every light by the near one, so
+    // only ambient reaches it.
+    fn shadowed_point_world(lights: Vec<Light>) -> World {
+        World::new().with_lights(lights).with_objects(vec![
+            Object::sphere(),
+            Object::sphere().with_transform(Matrix::translation(0.0, 0.0, 10.0)),
+        ])
+    }
+
+    fn shade_shadowed_point(world: &World) -> Color {
+        let r = Ray::new(Tuple::point(0.0, 0.0, 5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, &world.objects[1]);
+        let comps = i.prepare_computations(&r, &vec![]);
+        world.shade_hit(&comps, 4)
+    }
+
+    #[test]
+    fn a_second_light_adds_no_ambient() {
+        let light = Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let other = Light::new(Tuple::point(0.0, 0.3, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let delta = 0.00001;
+
+        let one = shade_shadowed_point(&shadowed_point_world(vec![light.clone()]));
+        let two = shade_shadowed_point(&shadowed_point_world(vec![light.clone(), other.clone()]));
+        assert!((one.0 - 0.1).abs() < delta);
+        assert!((two.0 - 0.1).abs() < delta);
+
+        let dim = Light::new(Tuple::point(0.0, 0.3, -10.0), Tuple::color(0.5, 0.5, 0.5));
+        let mixed = shade_shadowed_point(&shadowed_point_world(vec![dim, light.clone()]));
+        assert!((mixed.0 - 0.1).abs() < delta);
+
+        let overridden = shadowed_point_world(vec![light.clone(), other]).with_ambient_light(Tuple::color(0.5, 0.5, 0.5));
+        assert!((shade_shadowed_point(&overridden).0 - 0.05).abs() < delta);
+
+        assert_eq!(shade_shadowed_point(&shadowed_point_world(vec![])), Colors::black());
+    }
+
+    #[test]
+    fn a_single_coloured_light_shades_as_material_lighting_does() {
+        let lights = [
+            Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(0.25, 0.25, 0.25)),
+            Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 0.0, 0.0)),
+            Light::from_temperature(Tuple::point(-10.0, 10.0, -10.0), 3200.0, 1.0),
+        ];
+        for light in lights {
+            let world = World::default_world().with_lights(vec![light.clone()]);
+            let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+            let shape = &world.objects[0];
+            let comps = Intersection::new(4.0, shape).prepare_computations(&r, &vec![]);
+            let expected = shape.get_material().lighting(shape, &light, comps.over_point, comps.eyev, comps.normalv, false);
+            let c = world.shade_hit(&comps, 4);
+            let epsilon = 1e-10;
+            assert!((c.0 - expected.0).abs() < epsilon);
+            assert!((c.1 - expected.1).abs() < epsilon);
+            assert!((c.2 - expected.2).abs() < epsilon);
+
+            // In shadow only the light-tinted ambient is left.
+            let behind = Light::new(Tuple::point(0.0, 0.0, -10.0), light.intensity());
+            let shadowed = shade_shadowed_point(&shadowed_point_world(vec![behind]));
+            let ambient = light.intensity() * 0.1;
+            assert!((shadowed.0 - ambient.0).abs() < epsilon);
+            assert!((shadowed.1 - ambient.1).abs() < epsilon);
+            assert!((shadowed.2 - ambient.2).abs() < epsilon);
+        }
+    }
+
+    #[test]
+    fn max_bounces_on_a_material_caps_the_world_depth() {
+        let wall = Object::plane()
+            .with_transform(Matrix::rotation_x(std::f64::consts::PI / 2.0).translate(0.0, 0.0, 5.0))
+            .with_material(Material::unlit(Tuple::color(0.0, 0.0, 1.0)));
+        let light = Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let scene = |glass: Material| {
+            World::new()
+                .with_objects(vec![Object::sphere().with_material(glass), wall.clone()])
+                .with_lights(vec![light.clone()])
+        };
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+
+        let capped = scene(Material::glass().with_max_bounces(1)).color_at(&r, 5);
+        let one_layer = scene(Material::glass()).color_at(&r, 1);
+        let full = scene(Material::glass()).color_at(&r, 5);
+        assert_eq!(capped, one_layer);
+        assert_ne!(capped, full);
+    }
+
+    #[test]
+    fn a_gradient_sky_colors_missed_rays_by_height() {
+        let horizon = Tuple::color(1.0, 1.0, 1.0);
+        let zenith = Tuple::color(0.5, 0.7, 1.0);
+        let w = World::new().with_sky(Sky::Gradient { horizon, zenith });
+        let origin = Tuple::point(0.0, 0.0, 0.0);
+
+        assert_eq!(w.color_at(&Ray::new(origin, Tuple::vector(0.0, 1.0, 0.0)), 4), zenith);
+        assert_eq!(w.color_at(&Ray::new(origin, Tuple::vector(0.0, 0.0, 1.0)), 4), horizon);
+        assert_eq!(w.color_at(&Ray::new(origin, Tuple::vector(0.0, -1.0, 0.0)), 4), horizon);
+
+        let halfway = w.color_at(&Ray::new(origin, Tuple::vector(0.0, 1.0, 3.0_f64.sqrt())), 4);
+        assert!((halfway.0 - 0.75).abs() < 1e-10);
+        assert!((halfway.1 - 0.85).abs() < 1e-10);
+    }
+
+    #[test]
+    fn ambient_scale_dims_the_fill_light_of_the_whole_scene() {
+        let light = Light::new(Tuple::point(0.0, 0.0, -10.0), Tuple::color(1.0, 1.0, 1.0));
+        let shadowed = |ambient_scale: f64| shade_shadowed_point(&shadowed_point_world(vec![light.clone()]).with_ambient_scale(ambient_scale));
+        assert_eq!(World::new().ambient_scale, 1.0);
+        assert_eq!(shadowed(0.0), Colors::black());
+        let half = shadowed(0.5);
+        assert!((half.0 - 0.05).abs() < 0.00001);
+
+        // At 1.0 a coloured light still shades as it always has: through
+        // Material::lighting, with the ambient tinted by that light.
+        let warm = Light::new(Tuple::point(-10.0, 10.0, -10.0), Tuple::color(0.9, 0.5, 0.2));
+        let world = World::default_world().with_lights(vec![warm.clone()]).with_ambient_scale(1.0);
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let shape = &world.objects[0];
+        let comps = Intersection::new(4.0, shape).prepare_computations(&r, &vec![]);
+        let expected = shape.get_material().lighting(shape, &warm, comps.over_point, comps.eyev, comps.normalv, false);
+        let c = world.shade_hit(&comps, 4);
+        let epsilon = 1e-10;
+        assert!((c.0 - expected.0).abs() < epsilon);
+        assert!((c.1 - expected.1).abs() < epsilon);
+        assert!((c.2 - expected.2).abs() < epsilon);
+    }
+
+    #[test]
+    fn layers_apply_to_objects_inside_groups() {
+        let mut inner = Object::group().with_transform(Matrix::translation(-3.0, 0.0, 0.0));
+        inner.add_child(Object::cube().with_layer(1));
+        inner.add_child(Object::cone());
+        let mut g = Object::group().with_transform(Matrix::translation(0.0, 0.0, 5.0));
+        g.add_child(Object::sphere());
+        g.add_child(Object::sphere().with_transform(Matrix::translation(3.0, 0.0, 0.0)).with_layer(1));
+        g.add_child(inner);
+        let w = World::new().with_objects(vec![g, Object::plane()]);
+
+        let one = w.on_layer(1);
+        assert_eq!(one.objects.len(), 1);
+        assert_eq!(one.objects[0].child_count(), 2);
+        assert_eq!(one.objects[0].descendant_count(), 3);
+        let r = Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(one.hit(&r).is_none());
+        let r = Ray::new(Tuple::point(3.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(one.hit(&r).map(|i| i.t), Some(4.0));
+
+        assert_eq!(w.on_layer(0).objects.len(), 2);
+        assert_eq!(w.on_layer(0).objects[0].descendant_count(), 3);
+        assert!(w.on_layer(2).objects.is_empty());
+    }
+
+    #[test]
+    fn without_a_sky_missed_rays_stay_black() {
+        let w = World::new();
+        assert_eq!(w.sky, Sky::None);
+        let r = Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(w.color_at(&r, 4), Colors::black());
+        assert_eq!(w.hit_color_at(&r), None);
+    }
+
+    #[test]
+    fn intersecting_while_excluding_an_object() {
+        let w = World::default_world();
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let outer = w.objects[0].id;
+        let inner = w.objects[1].id;
+
+        let xs = w.intersect_excluding(&r, outer);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.5);
+        assert_eq!(xs[1].t, 5.5);
+        assert!(xs.iter().all(|i| i.object.id == inner));
+
+        assert_eq!(w.intersect_excluding(&r, usize::MAX).len(), 4);
+    }
+
+    #[test]
+    fn excluding_a_child_keeps_its_siblings() {
+        let mut g = Object::group();
+        g.add_child(Object::sphere());
+        g.add_child(Object::sphere().with_transform(Matrix::translation(0.0, 0.0, 5.0)));
+        let first = g.get_children()[0].id;
+        let w = World::new().with_objects(vec![g]);
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = w.intersect_excluding(&r, first);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 9.0);
+        assert_eq!(xs[1].t, 11.0);
+    }
+
+    // The wall's colour is its x coordinate scaled into [0, 1), so the red
+    // channel seen through a sphere says where the ray landed.
+    fn wall_behind(sphere: Material) -> World {
+        let wall = Object::plane()
+            .with_transform(Matrix::rotation_x(std::f64::consts::PI / 2.0).translate(0.0, 0.0, 5.0))
+            .with_material(Material::new()
+                .with_pattern(Pattern::gradient(Colors::black(), Colors::red()).scale(10.0, 1.0, 1.0).translate(-5.0, 0.0, 0.0))
+                .with_ambient(1.0)
+                .with_diffuse(0.0)
+                .with_specular(0.0));
+        let sphere = Object::sphere().with_material(sphere
+            .with_ambient(0.0)
+            .with_diffuse(0.0)
+            .with_specular(0.0)
+            .with_transparency(1.0)
+            .with_refractive_index(1.5));
+        World::new()
+            .with_objects(vec![wall, sphere])
+            .with_lights(vec![Light::new(Tuple::point(0.0, 10.0, -10.0), Tuple::color(1.0, 1.0, 1.0))])
+    }
+
+    #[test]
+    fn color_at_refracts_a_transparent_first_hit_through_the_full_intersection_list() {
+        let w = wall_behind(Material::new());
+        let r = Ray::new(Tuple::point(0.5, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+        let comps = xs.hit().unwrap().prepare_computations_with_bias(&r, &xs, w.shadow_bias);
+        assert_eq!((comps.n1, comps.n2), (1.0, 1.5));
+        assert_eq!(w.color_at(&r, 5), w.shade_hit(&comps, 5));
+    }
+
+    #[test]
+    fn a_thin_shell_displaces_the_background_less_than_a_solid_sphere() {
+        let r = Ray::new(Tuple::point(0.5, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let direct = (0.5 + 5.0) / 10.0;
+        let solid = wall_behind(Material::new()).color_at(&r, 5).0;
+        let thin = wall_behind(Material::new().with_thin(true)).color_at(&r, 5).0;
+        assert!((thin - direct).abs() < 1e-6);
+        assert!((solid - direct).abs() > 0.01);
+    }
+
+    #[test]
+    fn a_diffuse_surface_casts_no_secondary_rays() {
+        let w = World::default_world();
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let before = w.color_at(&r, 4);
+        let counters = RenderCounters::new();
+        let color = w.color_at_with(&r, 4, &mut CenteredSequence, Some(&counters));
+        let stats = counters.snapshot();
+        assert_eq!(color, before);
+        assert_eq!(stats.reflection_rays, 0);
+        assert_eq!(stats.refraction_rays, 0);
+        assert_eq!(stats.shadow_rays, 1);
+    }
+
+    #[test]
+    fn only_the_bouncing_part_of_a_material_recurses() {
+        let mirror = Object::plane()
+            .with_material(Material::new().with_reflectivity(0.5))
+            .with_transform(Matrix::translation(0.0, -1.0, 0.0));
+        let w = World::default_world().with_objects(vec![mirror]);
+        let r = Ray::new(Tuple::point(0.0, 0.0, -3.0), Tuple::vector(0.0, -2_f64.sqrt() / 2.0, 2_f64.sqrt() / 2.0));
+        let counters = RenderCounters::new();
+        w.color_at_with(&r, 4, &mut CenteredSequence, Some(&counters));
+        let stats = counters.snapshot();
+        assert_eq!(stats.reflection_rays, 1);
+        assert_eq!(stats.refraction_rays, 0);
+    }
+}